@@ -40,10 +40,10 @@ use crate::util::check_solution;
 
 #[test]
 fn test_part_1() {
-    check_solution(&DAY_02_SAMPLE_INPUT, 101, &part_1);
+    check_solution(DAY_02_SAMPLE_INPUT, 101, &part_1);
 }
 
 #[test]
 fn test_part_2() {
-    check_solution(&DAY_02_SAMPLE_INPUT, 48, &part_2);
+    check_solution(DAY_02_SAMPLE_INPUT, 48, &part_2);
 }
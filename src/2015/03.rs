@@ -1,17 +1,12 @@
+use crate::util::point::{Direction, Point2};
 use crate::util::{AdventResult, AdventSolution};
 
 pub fn part_1(input: &str) -> AdventResult {
-    let mut position = (0, 0);
+    let mut position = Point2::ORIGIN;
     let mut visited = std::collections::HashSet::new();
-    visited.insert((0, 0));
+    visited.insert(position);
     for step in input.chars() {
-        match step {
-            '>' => position.0 += 1,
-            '<' => position.0 -= 1,
-            '^' => position.1 += 1,
-            'v' => position.1 -= 1,
-            c => return Err(format!("Found bad character: '{}'", c)),
-        };
+        position += Direction::try_from(step)?.unit_vector();
         visited.insert(position);
     }
     Ok(AdventSolution::from(visited.len()))
@@ -24,13 +19,13 @@ enum Actor {
 
 /// This solution is due to Brady Butler (https://github.com/mbbutler).
 pub fn part_2(input: &str) -> AdventResult {
-    let mut santa = (0, 0);
-    let mut robot = (0, 0);
+    let mut santa = Point2::ORIGIN;
+    let mut robot = Point2::ORIGIN;
     let mut visited = std::collections::HashSet::new();
-    visited.insert((0, 0));
+    visited.insert(Point2::ORIGIN);
     let mut actor: Actor = Actor::Santa;
     for step in input.chars() {
-        let mut position = match actor {
+        let position = match actor {
             Actor::Santa => {
                 actor = Actor::Robot;
                 &mut santa
@@ -40,13 +35,7 @@ pub fn part_2(input: &str) -> AdventResult {
                 &mut robot
             }
         };
-        match step {
-            '>' => position.0 += 1,
-            '<' => position.0 -= 1,
-            '^' => position.1 += 1,
-            'v' => position.1 -= 1,
-            c => return Err(format!("Found bad character: '{}'", c)),
-        };
+        *position += Direction::try_from(step)?.unit_vector();
         visited.insert(*position);
     }
     Ok(AdventSolution::from(visited.len()))
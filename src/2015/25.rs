@@ -1,5 +1,6 @@
 use regex::Regex;
 
+use crate::util::number_theory::mod_pow;
 use crate::util::{AdventResult, AdventSolution};
 
 pub fn part_1(input: &str) -> AdventResult {
@@ -9,18 +10,14 @@ pub fn part_1(input: &str) -> AdventResult {
         caps[1].parse::<i64>().unwrap(),
         caps[2].parse::<i64>().unwrap(),
     );
-    let mut num_iterations = ((row + column - 2).pow(2) + (row + column - 2) + 2) / 2 + column - 2;
-    // The pattern repeats, so skip as many complete cycles as we can.
-    while num_iterations > 16_777_196 {
-        num_iterations -= 16_777_196;
-    }
+    let num_iterations = ((row + column - 2).pow(2) + (row + column - 2) + 2) / 2 + column - 2;
 
-    let mut code = 20_151_125_u64;
+    // Each code is the previous one times m, so we can jump straight to the
+    // one we want.
+    let first_code = 20_151_125_u64;
     let m = 252_533;
     let n = 33_554_393;
-    for _ in 0..num_iterations {
-        code = (code * m) % n;
-    }
+    let code = (first_code * mod_pow(m, num_iterations as u64, n).unwrap()) % n;
 
     Ok(AdventSolution::from(code))
 }
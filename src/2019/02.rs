@@ -0,0 +1,63 @@
+use super::intcode::{parse_program, Intcode};
+use crate::util::{AdventResult, AdventSolution};
+
+/// The output that part 2 is looking for
+const TARGET: i64 = 19_690_720;
+
+/// Run the program with a noun and verb in addresses 1 and 2, and return what
+/// ends up in address 0.
+fn run_with_noun_verb(program: &[i64], noun: i64, verb: i64) -> Result<i64, String> {
+    let mut computer = Intcode::new(program.to_vec());
+    computer.write(1, noun);
+    computer.write(2, verb);
+    computer.run_with_inputs(&[])?;
+    Ok(computer.read(0))
+}
+
+pub fn part_1(input: &str) -> AdventResult {
+    let program = parse_program(input)?;
+    Ok(AdventSolution::from(run_with_noun_verb(&program, 12, 2)?))
+}
+
+pub fn part_2(input: &str) -> AdventResult {
+    let program = parse_program(input)?;
+    for noun in 0..=99 {
+        for verb in 0..=99 {
+            // Some nouns and verbs point the program at garbage, so errors
+            // just rule them out.
+            if run_with_noun_verb(&program, noun, verb) == Ok(TARGET) {
+                return Ok(AdventSolution::from(100 * noun + verb));
+            }
+        }
+    }
+    Err(format!(
+        "Failed to find a noun and verb that produce {}",
+        TARGET
+    ))
+}
+
+#[cfg(test)]
+use crate::util::check_solution;
+
+#[test]
+fn test_run_with_noun_verb() {
+    let program = parse_program("1,9,10,3,2,3,11,0,99,30,40,50").unwrap();
+    assert_eq!(run_with_noun_verb(&program, 9, 10), Ok(3500));
+}
+
+#[test]
+fn test_part_2() {
+    // This program adds the values at the noun and the verb, and only 50 and
+    // 60 add up to the target.
+    let mut program = vec![0; 100];
+    program[..5].copy_from_slice(&[1, 0, 0, 0, 99]);
+    program[50] = 19_690_000;
+    program[60] = 720;
+    let input = program
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    check_solution(&input, 5060, &part_2);
+    assert!(part_2("1,0,0,0,99").is_err());
+}
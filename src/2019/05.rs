@@ -0,0 +1,45 @@
+use super::intcode::Intcode;
+use crate::util::{AdventResult, AdventSolution};
+
+/// Run the diagnostic program for the system with the given ID. Every output
+/// but the last is a test result that should be 0, and the last one is the
+/// diagnostic code.
+fn run_diagnostics(input: &str, system_id: i64) -> AdventResult {
+    let mut computer: Intcode = input.parse()?;
+    let outputs = computer.run_with_inputs(&[system_id])?;
+    match outputs.split_last() {
+        Some((code, tests)) if tests.iter().all(|t| *t == 0) => Ok(AdventSolution::from(*code)),
+        Some(_) => Err(format!("Diagnostic tests failed: {:?}", outputs)),
+        None => Err("Diagnostic program produced no output".to_string()),
+    }
+}
+
+pub fn part_1(input: &str) -> AdventResult {
+    run_diagnostics(input, 1)
+}
+
+pub fn part_2(input: &str) -> AdventResult {
+    run_diagnostics(input, 5)
+}
+
+#[cfg(test)]
+const DAY_05_SAMPLE_INPUT: &str = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,\
+    1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,\
+    999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+
+#[cfg(test)]
+use crate::util::check_solution;
+
+#[test]
+fn test_part_1() {
+    // Echo the input after passing one test
+    check_solution("104,0,3,0,4,0,99", 1, &part_1);
+    assert!(part_1("104,1,3,0,4,0,99").is_err());
+    assert!(part_1("99").is_err());
+}
+
+#[test]
+fn test_part_2() {
+    // The sample outputs 999 for inputs below 8.
+    check_solution(DAY_05_SAMPLE_INPUT, 999, &part_2);
+}
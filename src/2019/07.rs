@@ -0,0 +1,84 @@
+use super::intcode::network::{run_network, Scheduling, Topology};
+use super::intcode::{parse_program, Intcode};
+use crate::util::combinatorics::permutations;
+use crate::util::{AdventResult, AdventSolution};
+
+/// Run a chain of amplifiers, each starting with one of the phase settings.
+/// The first gets a signal of 0 and each passes its output to the next.
+fn run_amplifiers(program: &[i64], phases: &[&i64], topology: Topology) -> Result<i64, String> {
+    let amplifiers = phases
+        .iter()
+        .map(|phase| {
+            let mut amplifier = Intcode::new(program.to_vec());
+            amplifier.push_input(**phase);
+            amplifier
+        })
+        .collect();
+    match run_network(amplifiers, &[0], topology, Scheduling::Cooperative)? {
+        Some(signal) => Ok(signal),
+        None => Err("Amplifiers didn't produce a signal".to_string()),
+    }
+}
+
+fn find_max_signal(input: &str, phases: &[i64], topology: Topology) -> AdventResult {
+    let program = parse_program(input)?;
+    let mut max_signal = None;
+    for order in permutations(phases) {
+        let signal = run_amplifiers(&program, &order, topology)?;
+        max_signal = max_signal.max(Some(signal));
+    }
+    match max_signal {
+        Some(s) => Ok(AdventSolution::from(s)),
+        None => Err("Failed to find any phase settings".to_string()),
+    }
+}
+
+pub fn part_1(input: &str) -> AdventResult {
+    find_max_signal(input, &[0, 1, 2, 3, 4], Topology::Pipeline)
+}
+
+pub fn part_2(input: &str) -> AdventResult {
+    // The last amplifier feeds back into the first until they all halt.
+    find_max_signal(input, &[5, 6, 7, 8, 9], Topology::Ring)
+}
+
+#[cfg(test)]
+use crate::util::check_solution;
+
+#[test]
+fn test_part_1() {
+    check_solution(
+        "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0",
+        43210,
+        &part_1,
+    );
+    check_solution(
+        "3,23,3,24,1002,24,10,24,1002,23,-1,23,\
+        101,5,23,23,1,24,23,23,4,23,99,0,0",
+        54321,
+        &part_1,
+    );
+    check_solution(
+        "3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,\
+        1002,33,7,33,1,33,31,31,1,32,31,31,4,31,99,0,0,0",
+        65210,
+        &part_1,
+    );
+}
+
+#[test]
+fn test_part_2() {
+    check_solution(
+        "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,\
+        27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5",
+        139629729,
+        &part_2,
+    );
+    check_solution(
+        "3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,\
+        -5,54,1105,1,12,1,53,54,53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,\
+        53,1001,56,-1,56,1005,56,6,99,0,0,0,0,10",
+        18216,
+        &part_2,
+    );
+}
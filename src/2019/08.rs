@@ -0,0 +1,109 @@
+use crate::util::ocr::ocr_str;
+use crate::util::{AdventResult, AdventSolution};
+
+const WIDTH: usize = 25;
+const HEIGHT: usize = 6;
+
+/// Split the image data into layers of `width * height` digits.
+fn parse_layers(input: &str, width: usize, height: usize) -> Result<Vec<Vec<u8>>, String> {
+    let mut digits = Vec::new();
+    for c in input.trim_end().chars() {
+        match c.to_digit(10) {
+            Some(d) => digits.push(d as u8),
+            None => return Err(format!("Found invalid pixel: '{}'", c)),
+        }
+    }
+    let size = width * height;
+    if digits.is_empty() || digits.len() % size != 0 {
+        return Err(format!(
+            "Image data must be a whole number of {}x{} layers (found {} pixels)",
+            width,
+            height,
+            digits.len()
+        ));
+    }
+    Ok(digits.chunks(size).map(|layer| layer.to_vec()).collect())
+}
+
+/// Check the image for corruption by finding the layer with the fewest 0
+/// digits and multiplying its number of 1 digits by its number of 2 digits.
+fn checksum(input: &str, width: usize, height: usize) -> AdventResult {
+    let layers = parse_layers(input, width, height)?;
+    let count = |layer: &[u8], digit: u8| layer.iter().filter(|d| **d == digit).count();
+    let layer = layers.iter().min_by_key(|layer| count(layer, 0)).unwrap();
+    Ok(AdventSolution::from(count(layer, 1) * count(layer, 2)))
+}
+
+/// Stack the layers, where each pixel is the first one that isn't transparent
+/// (2), and draw the result with `#` for white (1) and `.` for black (0).
+fn decode_image(input: &str, width: usize, height: usize) -> Result<String, String> {
+    let layers = parse_layers(input, width, height)?;
+    let mut picture = String::new();
+    for row in 0..height {
+        for col in 0..width {
+            let pixel = layers
+                .iter()
+                .map(|layer| layer[row * width + col])
+                .find(|d| *d != 2);
+            picture.push(if pixel == Some(1) { '#' } else { '.' });
+        }
+        picture.push('\n');
+    }
+    Ok(picture)
+}
+
+pub fn part_1(input: &str) -> AdventResult {
+    checksum(input, WIDTH, HEIGHT)
+}
+
+/// The image shows the password in block letters. If they can't be read, the
+/// picture itself is the answer.
+pub fn part_2(input: &str) -> AdventResult {
+    let picture = decode_image(input, WIDTH, HEIGHT)?;
+    match ocr_str(&picture) {
+        Ok(text) if !text.is_empty() => Ok(AdventSolution::from(text)),
+        _ => Ok(AdventSolution::Display(picture)),
+    }
+}
+
+#[cfg(test)]
+use crate::util::check_solution;
+
+#[test]
+fn test_part_1() {
+    check_solution("123456789012", 1, &|input| checksum(input, 3, 2));
+    check_solution("000111222012\n", 4, &|input| checksum(input, 3, 2));
+    assert!(checksum("12345678901", 3, 2).is_err());
+    assert!(checksum("1234567890x2", 3, 2).is_err());
+}
+
+#[test]
+fn test_part_2() {
+    assert_eq!(
+        decode_image("0222112222120000", 2, 2),
+        Ok(".#\n#.\n".to_string())
+    );
+    // "HI" drawn on a transparent layer over one that fills in the rest.
+    let letters = "\
+        1001011100000000000000000\
+        1001001000000000000000000\
+        1111001000000000000000000\
+        1001001000000000000000000\
+        1001001000000000000000000\
+        1001011100000000000000000";
+    let top = letters.replace('0', "2");
+    let bottom = "0".repeat(WIDTH * HEIGHT);
+    check_solution(&(top + &bottom), "HI", &part_2);
+    // Something that isn't letters comes back as a picture.
+    let mut blob = "1".repeat(WIDTH * 2);
+    blob.push_str(&"0".repeat(WIDTH * 4));
+    check_solution(
+        &blob,
+        AdventSolution::Display(format!(
+            "{0}\n{0}\n{1}\n{1}\n{1}\n{1}\n",
+            "#".repeat(WIDTH),
+            ".".repeat(WIDTH)
+        )),
+        &part_2,
+    );
+}
@@ -0,0 +1,43 @@
+use super::intcode::fast::FastIntcode;
+use crate::util::{AdventResult, AdventSolution};
+
+/// Run the BOOST program in the given mode. It outputs a single keycode if
+/// everything works, and otherwise lists the opcodes that it found broken.
+fn run_boost(input: &str, mode: i64) -> AdventResult {
+    // Sensor boost mode runs for a long time, so it's worth decoding ahead.
+    let mut computer = FastIntcode::new(input.parse()?);
+    match computer.run_with_inputs(&[mode])?[..] {
+        [keycode] => Ok(AdventSolution::from(keycode)),
+        ref outputs => Err(format!("BOOST reported malfunctions: {:?}", outputs)),
+    }
+}
+
+pub fn part_1(input: &str) -> AdventResult {
+    run_boost(input, 1)
+}
+
+pub fn part_2(input: &str) -> AdventResult {
+    run_boost(input, 2)
+}
+
+#[cfg(test)]
+use crate::util::check_solution;
+
+#[test]
+fn test_part_1() {
+    check_solution("104,1125899906842624,99", 1125899906842624_i64, &part_1);
+    // Read the mode and output it again, using relative mode throughout.
+    check_solution("109,10,203,0,204,0,99", 1, &part_1);
+    // The quine outputs its own sixteen values, which isn't a keycode.
+    assert!(part_1("109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99").is_err());
+}
+
+#[test]
+fn test_part_2() {
+    check_solution("109,10,203,0,204,0,99", 2, &part_2);
+    check_solution(
+        "1102,34915192,34915192,7,4,7,99,0",
+        1219070632396864_i64,
+        &part_2,
+    );
+}
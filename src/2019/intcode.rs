@@ -0,0 +1,862 @@
+//! The Intcode computer that many of the 2019 puzzles are built on.
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::mpsc::{Receiver, Sender};
+
+pub mod ascii;
+pub mod assemble;
+pub mod debug;
+pub mod disassemble;
+pub mod fast;
+pub mod memory;
+pub mod network;
+pub mod profile;
+
+use memory::Memory;
+
+/// Programs can write past the end of their memory, which grows to fit, but
+/// addresses beyond this are treated as a bug in the program rather than
+/// something to allocate.
+pub const MAX_MEMORY: usize = 1 << 24;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Opcode {
+    Add,
+    Multiply,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustRelativeBase,
+    Halt,
+}
+
+impl Opcode {
+    pub const ALL: [Opcode; 10] = [
+        Opcode::Add,
+        Opcode::Multiply,
+        Opcode::Input,
+        Opcode::Output,
+        Opcode::JumpIfTrue,
+        Opcode::JumpIfFalse,
+        Opcode::LessThan,
+        Opcode::Equals,
+        Opcode::AdjustRelativeBase,
+        Opcode::Halt,
+    ];
+
+    pub fn from_code(code: i64) -> Option<Opcode> {
+        Opcode::ALL.into_iter().find(|op| op.code() == code)
+    }
+
+    /// The number in the last two digits of an instruction.
+    pub fn code(self) -> i64 {
+        match self {
+            Opcode::Add => 1,
+            Opcode::Multiply => 2,
+            Opcode::Input => 3,
+            Opcode::Output => 4,
+            Opcode::JumpIfTrue => 5,
+            Opcode::JumpIfFalse => 6,
+            Opcode::LessThan => 7,
+            Opcode::Equals => 8,
+            Opcode::AdjustRelativeBase => 9,
+            Opcode::Halt => 99,
+        }
+    }
+
+    /// The short name used in assembly listings.
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Opcode::Add => "add",
+            Opcode::Multiply => "mul",
+            Opcode::Input => "in",
+            Opcode::Output => "out",
+            Opcode::JumpIfTrue => "jnz",
+            Opcode::JumpIfFalse => "jz",
+            Opcode::LessThan => "lt",
+            Opcode::Equals => "eq",
+            Opcode::AdjustRelativeBase => "arb",
+            Opcode::Halt => "hlt",
+        }
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Opcode> {
+        Opcode::ALL.into_iter().find(|op| op.mnemonic() == mnemonic)
+    }
+
+    pub fn parameter_count(self) -> usize {
+        match self {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => 3,
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => 2,
+            Opcode::Input | Opcode::Output | Opcode::AdjustRelativeBase => 1,
+            Opcode::Halt => 0,
+        }
+    }
+
+    /// Which parameter, if any, is the address the instruction writes to.
+    pub fn write_parameter(self) -> Option<usize> {
+        match self {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => Some(2),
+            Opcode::Input => Some(0),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// The parameter is the address of the value.
+    Position,
+    /// The parameter is the value.
+    Immediate,
+    /// The parameter is an offset from the relative base.
+    Relative,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    /// The modes of the parameters, in order. Only the first
+    /// `opcode.parameter_count()` are meaningful, and the rest are always
+    /// [`Mode::Position`].
+    pub modes: [Mode; 3],
+}
+
+impl Instruction {
+    pub fn len(&self) -> usize {
+        1 + self.opcode.parameter_count()
+    }
+}
+
+/// Split an instruction into its opcode and parameter modes. `address` is only
+/// used for error messages.
+///
+/// A mode digit for a parameter that the opcode doesn't have is an error, even
+/// though it would never be used. Otherwise `1104` would mean the same as
+/// `104`, and couldn't be written back out the way it was read.
+pub fn decode(value: i64, address: usize) -> Result<Instruction, IntcodeError> {
+    let code = value.rem_euclid(100);
+    let opcode = match Opcode::from_code(code) {
+        Some(op) if value >= 0 => op,
+        _ => {
+            return Err(IntcodeError::InvalidOpcode {
+                address,
+                opcode: value,
+            })
+        }
+    };
+    let mut modes = [Mode::Position; 3];
+    let mut mode_digits = value / 100;
+    for (idx, mode) in modes.iter_mut().enumerate().take(opcode.parameter_count()) {
+        let digit = mode_digits % 10;
+        *mode = match digit {
+            0 => Mode::Position,
+            1 => Mode::Immediate,
+            2 => Mode::Relative,
+            _ => {
+                return Err(IntcodeError::InvalidMode {
+                    address,
+                    mode: digit,
+                })
+            }
+        };
+        if opcode.write_parameter() == Some(idx) && *mode == Mode::Immediate {
+            return Err(IntcodeError::ImmediateWrite { address });
+        }
+        mode_digits /= 10;
+    }
+    // Leftover digits mean a mode for a parameter that doesn't exist.
+    if mode_digits != 0 {
+        return Err(IntcodeError::InvalidMode {
+            address,
+            mode: mode_digits,
+        });
+    }
+    Ok(Instruction { opcode, modes })
+}
+
+/// Why a program stopped running.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Halted,
+    /// The program is waiting at an input instruction. It picks up from there
+    /// once there's input to read.
+    NeedsInput,
+    Output(i64),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IntcodeError {
+    Parse(String),
+    InvalidOpcode {
+        address: usize,
+        opcode: i64,
+    },
+    InvalidMode {
+        address: usize,
+        mode: i64,
+    },
+    /// An instruction tried to use a negative address, or one past
+    /// [`MAX_MEMORY`].
+    InvalidAddress {
+        address: usize,
+        target: i64,
+    },
+    /// An instruction's write parameter was in immediate mode.
+    ImmediateWrite {
+        address: usize,
+    },
+    /// The program asked for input when none was left.
+    MissingInput {
+        address: usize,
+    },
+    /// Every machine in a network is waiting for input that will never come.
+    Deadlock,
+    InvalidSnapshot(String),
+}
+
+impl Display for IntcodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntcodeError::Parse(s) => write!(f, "Failed to parse Intcode program: {}", s),
+            IntcodeError::InvalidOpcode { address, opcode } => {
+                write!(f, "Found invalid opcode {} at address {}", opcode, address)
+            }
+            IntcodeError::InvalidMode { address, mode } => write!(
+                f,
+                "Found invalid parameter mode {} at address {}",
+                mode, address
+            ),
+            IntcodeError::InvalidAddress { address, target } => write!(
+                f,
+                "Instruction at address {} used invalid address {}",
+                address, target
+            ),
+            IntcodeError::ImmediateWrite { address } => write!(
+                f,
+                "Instruction at address {} writes to an immediate parameter",
+                address
+            ),
+            IntcodeError::MissingInput { address } => write!(
+                f,
+                "Instruction at address {} needs input but there's none left",
+                address
+            ),
+            IntcodeError::Deadlock => write!(f, "Every machine is waiting for input"),
+            IntcodeError::InvalidSnapshot(s) => {
+                write!(f, "Failed to load Intcode snapshot: {}", s)
+            }
+        }
+    }
+}
+
+impl From<IntcodeError> for String {
+    fn from(e: IntcodeError) -> Self {
+        e.to_string()
+    }
+}
+
+pub fn parse_program(input: &str) -> Result<Vec<i64>, IntcodeError> {
+    input
+        .trim()
+        .split(',')
+        .map(|value| {
+            value
+                .trim()
+                .parse::<i64>()
+                .map_err(|e| IntcodeError::Parse(format!("'{}': {}", value, e)))
+        })
+        .collect()
+}
+
+/// Somewhere for a machine to get input from when its own queue runs dry.
+pub trait IntcodeInput {
+    /// The next input value, or `None` if there isn't one (yet).
+    fn next_input(&mut self) -> Option<i64>;
+}
+
+/// Somewhere for a machine to send its output.
+pub trait IntcodeOutput {
+    fn write_output(&mut self, value: i64);
+}
+
+impl IntcodeInput for VecDeque<i64> {
+    fn next_input(&mut self) -> Option<i64> {
+        self.pop_front()
+    }
+}
+
+/// Blocks until a value arrives, and gives up once every sender is gone.
+impl IntcodeInput for Receiver<i64> {
+    fn next_input(&mut self) -> Option<i64> {
+        self.recv().ok()
+    }
+}
+
+impl<F: FnMut() -> Option<i64>> IntcodeInput for F {
+    fn next_input(&mut self) -> Option<i64> {
+        self()
+    }
+}
+
+impl IntcodeOutput for VecDeque<i64> {
+    fn write_output(&mut self, value: i64) {
+        self.push_back(value);
+    }
+}
+
+impl IntcodeOutput for Vec<i64> {
+    fn write_output(&mut self, value: i64) {
+        self.push(value);
+    }
+}
+
+/// Output sent after the receiver has gone is dropped, since nothing could
+/// ever read it.
+impl IntcodeOutput for Sender<i64> {
+    fn write_output(&mut self, value: i64) {
+        let _ = self.send(value);
+    }
+}
+
+impl<F: FnMut(i64)> IntcodeOutput for F {
+    fn write_output(&mut self, value: i64) {
+        self(value)
+    }
+}
+
+/// The first line of a snapshot, so that other files aren't mistaken for one.
+const SNAPSHOT_HEADER: &str = "intcode snapshot v1";
+
+/// An Intcode machine. Cloning one is cheap, since the clones share memory
+/// until they write to it.
+#[derive(Clone, Debug)]
+pub struct Intcode {
+    memory: Memory,
+    ip: usize,
+    relative_base: i64,
+    inputs: VecDeque<i64>,
+}
+
+impl FromStr for Intcode {
+    type Err = IntcodeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Intcode::new(parse_program(s)?))
+    }
+}
+
+impl Intcode {
+    pub fn new(program: Vec<i64>) -> Self {
+        Intcode {
+            memory: Memory::from(program),
+            ip: 0,
+            relative_base: 0,
+            inputs: VecDeque::new(),
+        }
+    }
+
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+
+    /// A copy of memory, up to the highest address that's been used.
+    pub fn memory(&self) -> Vec<i64> {
+        self.memory.to_vec()
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.inputs.push_back(value);
+    }
+
+    pub fn pending_inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Read memory. Addresses past the end of the program hold 0.
+    pub fn read(&self, address: usize) -> i64 {
+        self.memory.read(address)
+    }
+
+    /// Write memory, growing it if needed.
+    pub fn write(&mut self, address: usize, value: i64) {
+        self.memory.write(address, value);
+    }
+
+    /// The address that a parameter refers to, for parameters that aren't
+    /// immediate.
+    fn parameter_address(
+        &self,
+        instruction: &Instruction,
+        idx: usize,
+    ) -> Result<usize, IntcodeError> {
+        let raw = self.read(self.ip + 1 + idx);
+        let target = match instruction.modes[idx] {
+            Mode::Relative => self.relative_base.wrapping_add(raw),
+            _ => raw,
+        };
+        match usize::try_from(target) {
+            Ok(a) if a < MAX_MEMORY => Ok(a),
+            _ => Err(IntcodeError::InvalidAddress {
+                address: self.ip,
+                target,
+            }),
+        }
+    }
+
+    fn parameter(&self, instruction: &Instruction, idx: usize) -> Result<i64, IntcodeError> {
+        match instruction.modes[idx] {
+            Mode::Immediate => Ok(self.read(self.ip + 1 + idx)),
+            _ => Ok(self.read(self.parameter_address(instruction, idx)?)),
+        }
+    }
+
+    /// Run a single instruction. Returns the status if the instruction halted
+    /// the program, produced output, or is waiting for input, and `None` if
+    /// the program can keep going.
+    pub fn step(&mut self) -> Result<Option<Status>, IntcodeError> {
+        let instruction = decode(self.read(self.ip), self.ip)?;
+        let param = |idx| self.parameter(&instruction, idx);
+        let mut next_ip = self.ip + instruction.len();
+        let mut status = None;
+        match instruction.opcode {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => {
+                let (a, b) = (param(0)?, param(1)?);
+                let value = match instruction.opcode {
+                    Opcode::Add => a.wrapping_add(b),
+                    Opcode::Multiply => a.wrapping_mul(b),
+                    Opcode::LessThan => (a < b) as i64,
+                    _ => (a == b) as i64,
+                };
+                let address = self.parameter_address(&instruction, 2)?;
+                self.write(address, value);
+            }
+            Opcode::Input => {
+                let address = self.parameter_address(&instruction, 0)?;
+                match self.inputs.pop_front() {
+                    Some(value) => self.write(address, value),
+                    // Stay on this instruction so it runs again once there's
+                    // input.
+                    None => return Ok(Some(Status::NeedsInput)),
+                }
+            }
+            Opcode::Output => status = Some(Status::Output(param(0)?)),
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                let (condition, target) = (param(0)?, param(1)?);
+                if (condition != 0) == (instruction.opcode == Opcode::JumpIfTrue) {
+                    next_ip = match usize::try_from(target) {
+                        Ok(t) => t,
+                        Err(_) => {
+                            return Err(IntcodeError::InvalidAddress {
+                                address: self.ip,
+                                target,
+                            })
+                        }
+                    };
+                }
+            }
+            Opcode::AdjustRelativeBase => {
+                self.relative_base = self.relative_base.wrapping_add(param(0)?);
+            }
+            // Stay on the halt so that running again halts again.
+            Opcode::Halt => return Ok(Some(Status::Halted)),
+        }
+        self.ip = next_ip;
+        Ok(status)
+    }
+
+    /// Run until the program halts, produces output, or needs input.
+    pub fn run(&mut self) -> Result<Status, IntcodeError> {
+        loop {
+            if let Some(status) = self.step()? {
+                return Ok(status);
+            }
+        }
+    }
+
+    /// Run until the program halts, feeding it `inputs` and collecting all of
+    /// its output.
+    pub fn run_with_inputs(&mut self, inputs: &[i64]) -> Result<Vec<i64>, IntcodeError> {
+        self.inputs.extend(inputs);
+        let mut outputs = Vec::new();
+        loop {
+            match self.run()? {
+                Status::Output(value) => outputs.push(value),
+                Status::Halted => return Ok(outputs),
+                Status::NeedsInput => return Err(IntcodeError::MissingInput { address: self.ip }),
+            }
+        }
+    }
+
+    /// Run until the program halts, or until it needs input and `input` has
+    /// none to give. Every output goes straight to `output`, so this never
+    /// returns [`Status::Output`].
+    pub fn run_with(
+        &mut self,
+        input: &mut impl IntcodeInput,
+        output: &mut impl IntcodeOutput,
+    ) -> Result<Status, IntcodeError> {
+        loop {
+            match self.run()? {
+                Status::Output(value) => output.write_output(value),
+                Status::NeedsInput => match input.next_input() {
+                    Some(value) => self.push_input(value),
+                    None => return Ok(Status::NeedsInput),
+                },
+                Status::Halted => return Ok(Status::Halted),
+            }
+        }
+    }
+
+    /// Save the machine's state as text that [`Intcode::from_snapshot`] can
+    /// load again.
+    pub fn to_snapshot(&self) -> String {
+        let join = |values: Vec<i64>| {
+            values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        format!(
+            "{}\nip {}\nrb {}\ninputs {}\nmemory {}\n",
+            SNAPSHOT_HEADER,
+            self.ip,
+            self.relative_base,
+            join(self.inputs.iter().copied().collect()),
+            join(self.memory())
+        )
+    }
+
+    pub fn from_snapshot(snapshot: &str) -> Result<Self, IntcodeError> {
+        let invalid = |s: String| IntcodeError::InvalidSnapshot(s);
+        let mut lines = snapshot.lines();
+        if lines.next() != Some(SNAPSHOT_HEADER) {
+            return Err(invalid(format!("Expected '{}' first", SNAPSHOT_HEADER)));
+        }
+        let mut field = |name: &str| match lines.next().and_then(|l| l.split_once(' ')) {
+            Some((n, value)) if n == name => Ok(value.trim()),
+            _ => Err(invalid(format!("Failed to find '{}'", name))),
+        };
+        let number = |name: &str, value: &str| {
+            value
+                .parse::<i64>()
+                .map_err(|_| invalid(format!("Failed to parse {} '{}'", name, value)))
+        };
+        let ip = number("ip", field("ip")?)?;
+        let relative_base = number("rb", field("rb")?)?;
+        let list = |value: &str| match value {
+            "" => Ok(Vec::new()),
+            v => parse_program(v).map_err(|e| invalid(e.to_string())),
+        };
+        let inputs = list(field("inputs")?)?;
+        let memory = list(field("memory")?)?;
+        let mut machine = Intcode::new(memory);
+        machine.ip =
+            usize::try_from(ip).map_err(|_| invalid(format!("Found negative ip {}", ip)))?;
+        machine.relative_base = relative_base;
+        machine.inputs.extend(inputs);
+        Ok(machine)
+    }
+}
+
+#[cfg(test)]
+fn run_program(program: &str, inputs: &[i64]) -> Result<Vec<i64>, IntcodeError> {
+    program.parse::<Intcode>()?.run_with_inputs(inputs)
+}
+
+#[test]
+fn test_decode() {
+    assert_eq!(
+        decode(1002, 0),
+        Ok(Instruction {
+            opcode: Opcode::Multiply,
+            modes: [Mode::Position, Mode::Immediate, Mode::Position]
+        })
+    );
+    assert_eq!(
+        decode(21101, 0),
+        Ok(Instruction {
+            opcode: Opcode::Add,
+            modes: [Mode::Immediate, Mode::Immediate, Mode::Relative]
+        })
+    );
+    assert_eq!(decode(99, 0).map(|i| i.len()), Ok(1));
+    assert_eq!(
+        decode(42, 7),
+        Err(IntcodeError::InvalidOpcode {
+            address: 7,
+            opcode: 42
+        })
+    );
+    assert_eq!(
+        decode(-1, 0),
+        Err(IntcodeError::InvalidOpcode {
+            address: 0,
+            opcode: -1
+        })
+    );
+    assert_eq!(
+        decode(304, 3),
+        Err(IntcodeError::InvalidMode {
+            address: 3,
+            mode: 3
+        })
+    );
+    assert_eq!(
+        decode(10001, 0),
+        Err(IntcodeError::ImmediateWrite { address: 0 })
+    );
+    assert!(decode(1000104, 0).is_err());
+    // Modes for parameters that the opcode doesn't have
+    for (value, mode) in [(1104, 1), (1099, 10), (20004, 20), (10005, 1)] {
+        assert_eq!(
+            decode(value, 0),
+            Err(IntcodeError::InvalidMode { address: 0, mode })
+        );
+    }
+}
+
+#[test]
+fn test_day_02_examples() {
+    let mut computer: Intcode = "1,9,10,3,2,3,11,0,99,30,40,50".parse().unwrap();
+    assert_eq!(computer.run(), Ok(Status::Halted));
+    assert_eq!(computer.read(0), 3500);
+    for (program, result) in [
+        ("1,0,0,0,99", vec![2, 0, 0, 0, 99]),
+        ("2,3,0,3,99", vec![2, 3, 0, 6, 99]),
+        ("2,4,4,5,99,0", vec![2, 4, 4, 5, 99, 9801]),
+        ("1,1,1,4,99,5,6,0,99", vec![30, 1, 1, 4, 2, 5, 6, 0, 99]),
+    ] {
+        let mut computer: Intcode = program.parse().unwrap();
+        assert_eq!(computer.run(), Ok(Status::Halted));
+        assert_eq!(computer.memory(), result);
+    }
+}
+
+#[test]
+fn test_day_05_examples() {
+    assert_eq!(run_program("3,0,4,0,99", &[37]), Ok(vec![37]));
+    // Negative numbers and immediate mode
+    let mut computer: Intcode = "1101,100,-1,4,0".parse().unwrap();
+    assert_eq!(computer.run(), Ok(Status::Halted));
+    assert_eq!(computer.read(4), 99);
+    // Comparisons and jumps, in position and immediate mode
+    for (program, input, output) in [
+        ("3,9,8,9,10,9,4,9,99,-1,8", 8, 1),
+        ("3,9,7,9,10,9,4,9,99,-1,8", 8, 0),
+        ("3,3,1108,-1,8,3,4,3,99", 7, 0),
+        ("3,3,1107,-1,8,3,4,3,99", 7, 1),
+        ("3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9", 0, 0),
+        ("3,3,1105,-1,9,1101,0,0,12,4,12,99,1", 5, 1),
+    ] {
+        assert_eq!(run_program(program, &[input]), Ok(vec![output]));
+    }
+    let larger = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,\
+        1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,\
+        999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+    assert_eq!(run_program(larger, &[7]), Ok(vec![999]));
+    assert_eq!(run_program(larger, &[8]), Ok(vec![1000]));
+    assert_eq!(run_program(larger, &[9]), Ok(vec![1001]));
+}
+
+#[test]
+fn test_day_09_examples() {
+    let quine = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+    assert_eq!(run_program(quine, &[]), Ok(parse_program(quine).unwrap()));
+    let output = run_program("1102,34915192,34915192,7,4,7,99,0", &[]).unwrap();
+    assert_eq!(output[0].to_string().len(), 16);
+    assert_eq!(
+        run_program("104,1125899906842624,99", &[]),
+        Ok(vec![1125899906842624])
+    );
+}
+
+#[test]
+fn test_input_and_errors() {
+    // Input that arrives later is picked up where the program left off.
+    let mut computer: Intcode = "3,0,4,0,99".parse().unwrap();
+    assert_eq!(computer.run(), Ok(Status::NeedsInput));
+    assert_eq!(computer.ip(), 0);
+    computer.push_input(5);
+    assert_eq!(computer.run(), Ok(Status::Output(5)));
+    assert_eq!(computer.run(), Ok(Status::Halted));
+    assert_eq!(computer.run(), Ok(Status::Halted));
+
+    assert_eq!(
+        run_program("3,0,99", &[]),
+        Err(IntcodeError::MissingInput { address: 0 })
+    );
+    assert_eq!(
+        run_program("1,0,0,0,42", &[]),
+        Err(IntcodeError::InvalidOpcode {
+            address: 4,
+            opcode: 42
+        })
+    );
+    assert_eq!(
+        run_program("4,-3,99", &[]),
+        Err(IntcodeError::InvalidAddress {
+            address: 0,
+            target: -3
+        })
+    );
+    assert_eq!(
+        run_program("1105,1,-1", &[]),
+        Err(IntcodeError::InvalidAddress {
+            address: 0,
+            target: -1
+        })
+    );
+    assert!(matches!(
+        "1,2,x".parse::<Intcode>(),
+        Err(IntcodeError::Parse(_))
+    ));
+    assert_eq!(
+        String::from(IntcodeError::InvalidMode {
+            address: 2,
+            mode: 7
+        }),
+        "Found invalid parameter mode 7 at address 2"
+    );
+}
+
+#[test]
+fn test_run_with() {
+    let doubler = "3,20,102,2,20,20,4,20,1105,1,0";
+    let mut computer: Intcode = doubler.parse().unwrap();
+    let mut inputs = VecDeque::from([1, 2, 3]);
+    let mut outputs = Vec::new();
+    assert_eq!(
+        computer.run_with(&mut inputs, &mut outputs),
+        Ok(Status::NeedsInput)
+    );
+    assert_eq!(outputs, vec![2, 4, 6]);
+
+    // Closures on both ends
+    let mut next = 10;
+    let mut total = 0;
+    let mut computer: Intcode = doubler.parse().unwrap();
+    let status = computer.run_with(
+        &mut || {
+            next -= 1;
+            (next > 0).then_some(next)
+        },
+        &mut |value| total += value,
+    );
+    assert_eq!(status, Ok(Status::NeedsInput));
+    assert_eq!(total, 2 * (1..10).sum::<i64>());
+
+    // Channels, where input runs out once every sender is gone
+    let (input_tx, mut input_rx) = std::sync::mpsc::channel();
+    let (mut output_tx, output_rx) = std::sync::mpsc::channel();
+    [4, 5].into_iter().for_each(|v| input_tx.send(v).unwrap());
+    drop(input_tx);
+    let mut computer: Intcode = doubler.parse().unwrap();
+    assert_eq!(
+        computer.run_with(&mut input_rx, &mut output_tx),
+        Ok(Status::NeedsInput)
+    );
+    drop(output_tx);
+    assert_eq!(output_rx.iter().collect::<Vec<_>>(), vec![8, 10]);
+}
+
+#[test]
+fn test_snapshots() {
+    let mut computer: Intcode = "109,-7,3,20,204,27,1105,1,2".parse().unwrap();
+    computer.push_input(4);
+    computer.push_input(5);
+    assert_eq!(computer.run(), Ok(Status::Output(4)));
+    let snapshot = computer.to_snapshot();
+    assert_eq!(
+        snapshot,
+        "intcode snapshot v1\nip 6\nrb -7\ninputs 5\n\
+        memory 109,-7,3,20,204,27,1105,1,2,0,0,0,0,0,0,0,0,0,0,0,4\n"
+    );
+    let mut restored = Intcode::from_snapshot(&snapshot).unwrap();
+    assert_eq!(
+        restored.run_with_inputs(&[6]),
+        computer.run_with_inputs(&[6])
+    );
+
+    let fresh =
+        Intcode::from_snapshot("intcode snapshot v1\nip 0\nrb 0\ninputs \nmemory 99").unwrap();
+    assert_eq!((fresh.pending_inputs(), fresh.memory()), (0, vec![99]));
+    for bad in [
+        "",
+        "ip 0\nrb 0\ninputs\nmemory 99",
+        "intcode snapshot v1\nrb 0\nip 0\ninputs \nmemory 99",
+        "intcode snapshot v1\nip -1\nrb 0\ninputs \nmemory 99",
+        "intcode snapshot v1\nip 0\nrb 0\ninputs x\nmemory 99",
+        "intcode snapshot v1\nip 0\nrb 0\ninputs 1",
+    ] {
+        assert!(matches!(
+            Intcode::from_snapshot(bad),
+            Err(IntcodeError::InvalidSnapshot(_))
+        ));
+    }
+}
+
+#[test]
+fn test_forks() {
+    // Fork a machine with a large memory many times over, as a search would.
+    let mut program = vec![0; 100_000];
+    program[..7].copy_from_slice(&[3, 99_999, 4, 99_999, 1105, 1, 0]);
+    let root = Intcode::new(program);
+    let forks: Vec<Intcode> = (0..1000)
+        .map(|i| {
+            let mut fork = root.clone();
+            fork.push_input(i);
+            assert_eq!(fork.run(), Ok(Status::Output(i)));
+            fork
+        })
+        .collect();
+    // Only the last page, which each fork wrote to, has been copied.
+    let pages = 100_000_usize.div_ceil(memory::PAGE_SIZE);
+    for fork in &forks {
+        assert_eq!(fork.memory.shared_pages(&root.memory), pages - 1);
+    }
+    assert_eq!(root.read(99_999), 0);
+    assert_eq!(forks[500].read(99_999), 500);
+}
+
+#[test]
+fn test_memory_grows() {
+    // Write past the end of the program, then read it back.
+    assert_eq!(run_program("1101,2,3,100,4,100,99", &[]), Ok(vec![5]));
+    // Relative mode reads and writes
+    assert_eq!(run_program("109,50,21101,4,5,0,204,0,99", &[]), Ok(vec![9]));
+}
+
+#[test]
+fn test_relative_base_stack() {
+    // Push the inputs onto a stack and pop them back off in reverse order.
+    let program = assemble::assemble(
+        "        arb  #100
+        push:   in   [rb+0]
+                arb  #1
+                add  @count #1 @count
+                lt   @count #3 @flag
+                jnz  @flag push
+        pop:    arb  #-1
+                out  [rb+0]
+                add  @count #-1 @count
+                jnz  @count pop
+                hlt
+        count:  data 0
+        flag:   data 0",
+    )
+    .unwrap();
+    assert_eq!(
+        Intcode::new(program).run_with_inputs(&[7, 8, 9]),
+        Ok(vec![9, 8, 7])
+    );
+}
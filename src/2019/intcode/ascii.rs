@@ -0,0 +1,191 @@
+//! Talk to Intcode programs that read and print ASCII text.
+
+use super::{Intcode, IntcodeError, Status};
+use std::io::{BufRead, Write};
+
+/// What a program printed before it stopped. Values that aren't ASCII, like
+/// the big number that many puzzles end with, are kept apart from the text.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AsciiOutput {
+    pub text: String,
+    pub values: Vec<i64>,
+}
+
+pub struct Ascii {
+    machine: Intcode,
+}
+
+impl Ascii {
+    pub fn new(machine: Intcode) -> Self {
+        Ascii { machine }
+    }
+
+    /// Queue a line of input, which gets a newline on the end.
+    pub fn send_line(&mut self, line: &str) -> Result<(), String> {
+        if !line.is_ascii() {
+            return Err(format!("Failed to send non-ASCII text '{}'", line));
+        }
+        line.bytes()
+            .chain([b'\n'])
+            .for_each(|b| self.machine.push_input(b as i64));
+        Ok(())
+    }
+
+    /// Run until the program halts or needs more input, collecting what it
+    /// prints.
+    pub fn run(&mut self) -> Result<(AsciiOutput, Status), IntcodeError> {
+        let mut output = AsciiOutput::default();
+        let status =
+            self.machine
+                .run_with(&mut || None, &mut |value| match u8::try_from(value) {
+                    Ok(b) if b.is_ascii() => output.text.push(b as char),
+                    _ => output.values.push(value),
+                })?;
+        Ok((output, status))
+    }
+}
+
+fn write_output(out: &mut dyn Write, output: &AsciiOutput) -> std::io::Result<()> {
+    write!(out, "{}", output.text)?;
+    for value in &output.values {
+        writeln!(out, "Non-ASCII output: {}", value)?;
+    }
+    out.flush()
+}
+
+/// Play a text-driven program, reading each line of input from `input`. The
+/// lines in `replay` go first, and they're echoed so that the output reads as
+/// if they'd been typed. Every line sent is also written to `record`, so that
+/// it can be replayed later.
+pub fn play(
+    ascii: &mut Ascii,
+    replay: &[String],
+    input: &mut dyn BufRead,
+    out: &mut dyn Write,
+    mut record: Option<&mut dyn Write>,
+) -> Result<(), String> {
+    let write_error = |e: std::io::Error| format!("Failed to write output: {}", e);
+    let mut replay = replay.iter();
+    loop {
+        let (output, status) = ascii.run()?;
+        write_output(out, &output).map_err(write_error)?;
+        if status == Status::Halted {
+            return Ok(());
+        }
+        let line = match replay.next() {
+            Some(line) => {
+                writeln!(out, "{}", line).map_err(write_error)?;
+                line.clone()
+            }
+            None => {
+                let mut line = String::new();
+                match input.read_line(&mut line) {
+                    Ok(0) => return Ok(()),
+                    Ok(_) => line.trim_end_matches(['\r', '\n']).to_string(),
+                    Err(e) => return Err(format!("Failed to read input: {}", e)),
+                }
+            }
+        };
+        ascii.send_line(&line)?;
+        if let Some(record) = record.as_mut() {
+            writeln!(record, "{}", line)
+                .map_err(|e| format!("Failed to write transcript: {}", e))?;
+        }
+    }
+}
+
+/// Prints a prompt, echoes each line it reads, and stops after two lines with
+/// a number that isn't ASCII.
+#[cfg(test)]
+const ECHO_PROGRAM: &str = "
+start:  out  #62
+        out  #10
+read:   in   @char
+        out  @char
+        eq   @char #10 @flag
+        jz   @flag read
+        add  @lines #1 @lines
+        eq   @lines #2 @flag
+        jz   @flag start
+        out  #1000
+        hlt
+char:   data 0
+flag:   data 0
+lines:  data 0";
+
+#[cfg(test)]
+fn echo_machine() -> Intcode {
+    Intcode::new(super::assemble::assemble(ECHO_PROGRAM).unwrap())
+}
+
+#[test]
+fn test_ascii() {
+    let mut ascii = Ascii::new(echo_machine());
+    let text_output = |text: &str| AsciiOutput {
+        text: text.to_string(),
+        values: Vec::new(),
+    };
+    assert_eq!(ascii.run(), Ok((text_output(">\n"), Status::NeedsInput)));
+    ascii.send_line("ab").unwrap();
+    assert_eq!(
+        ascii.run(),
+        Ok((text_output("ab\n>\n"), Status::NeedsInput))
+    );
+    assert!(ascii.send_line("é").is_err());
+    ascii.send_line("c").unwrap();
+    let output = AsciiOutput {
+        text: "c\n".to_string(),
+        values: vec![1000],
+    };
+    assert_eq!(ascii.run(), Ok((output, Status::Halted)));
+}
+
+#[test]
+fn test_play_and_replay() {
+    let mut out = Vec::new();
+    let mut transcript = Vec::new();
+    play(
+        &mut Ascii::new(echo_machine()),
+        &[],
+        &mut "hello\r\nworld\nunused\n".as_bytes(),
+        &mut out,
+        Some(&mut transcript),
+    )
+    .unwrap();
+    let played = String::from_utf8(out).unwrap();
+    assert_eq!(played, ">\nhello\n>\nworld\nNon-ASCII output: 1000\n");
+    assert_eq!(transcript, b"hello\nworld\n");
+
+    // Replaying the transcript echoes the lines, so the output matches what
+    // was on the screen while playing.
+    let replay: Vec<String> = String::from_utf8(transcript)
+        .unwrap()
+        .lines()
+        .map(|l| l.to_string())
+        .collect();
+    let mut out = Vec::new();
+    play(
+        &mut Ascii::new(echo_machine()),
+        &replay,
+        &mut "".as_bytes(),
+        &mut out,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        ">\nhello\nhello\n>\nworld\nworld\nNon-ASCII output: 1000\n"
+    );
+
+    // Running out of input just stops.
+    let mut out = Vec::new();
+    play(
+        &mut Ascii::new(echo_machine()),
+        &replay[..1],
+        &mut "".as_bytes(),
+        &mut out,
+        None,
+    )
+    .unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), ">\nhello\nhello\n>\n");
+}
@@ -0,0 +1,253 @@
+//! Write Intcode programs by hand, in the same syntax that the disassembler
+//! prints.
+//!
+//! ```text
+//! ; Count down from the input to 1.
+//! start:  in   @count
+//! loop:   out  @count
+//!         add  @count #-1 @count
+//!         jnz  @count loop
+//!         hlt
+//! count:  data 0
+//! ```
+//!
+//! Each line has an instruction and its operands, separated by spaces or
+//! commas. Operands are `@120` for position mode, `#5` for immediate mode,
+//! and `[rb+3]` for relative mode. Anywhere a number can go, a label can go
+//! instead, and a bare label is the same as `#label`. `data` puts its values
+//! straight into the program. Labels end with `:`, comments start with `;`,
+//! and a number before the instruction is taken to be its address, which has
+//! to match where it ends up.
+
+use super::{Mode, Opcode};
+use std::collections::HashMap;
+
+enum Statement<'a> {
+    Instruction(Opcode, Vec<&'a str>),
+    Data(Vec<&'a str>),
+}
+
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && name != "rb"
+}
+
+fn parse_value(word: &str, labels: &HashMap<&str, usize>) -> Result<i64, String> {
+    match labels.get(word) {
+        Some(address) => Ok(*address as i64),
+        None if is_label(word) => Err(format!("Failed to find label '{}'", word)),
+        None => word
+            .parse::<i64>()
+            .map_err(|_| format!("Failed to parse value '{}'", word)),
+    }
+}
+
+fn parse_operand(word: &str, labels: &HashMap<&str, usize>) -> Result<(Mode, i64), String> {
+    if let Some(value) = word.strip_prefix('#') {
+        Ok((Mode::Immediate, parse_value(value, labels)?))
+    } else if let Some(value) = word.strip_prefix('@') {
+        Ok((Mode::Position, parse_value(value, labels)?))
+    } else if let Some(offset) = word.strip_prefix("[rb").and_then(|w| w.strip_suffix(']')) {
+        let offset = match offset.strip_prefix('+') {
+            Some(o) => o.parse::<i64>(),
+            None if offset.is_empty() => Ok(0),
+            None => offset.parse::<i64>(),
+        };
+        match offset {
+            Ok(o) => Ok((Mode::Relative, o)),
+            Err(_) => Err(format!("Failed to parse relative operand '{}'", word)),
+        }
+    } else {
+        Ok((Mode::Immediate, parse_value(word, labels)?))
+    }
+}
+
+fn encode(
+    opcode: Opcode,
+    operands: &[&str],
+    labels: &HashMap<&str, usize>,
+) -> Result<Vec<i64>, String> {
+    let mut instruction = opcode.code();
+    let mut parameters = Vec::new();
+    for (idx, (word, scale)) in operands.iter().zip([100, 1000, 10000]).enumerate() {
+        let (mode, value) = parse_operand(word, labels)?;
+        instruction += scale
+            * match mode {
+                Mode::Position => 0,
+                Mode::Immediate => 1,
+                Mode::Relative => 2,
+            };
+        if mode == Mode::Immediate && opcode.write_parameter() == Some(idx) {
+            return Err(format!(
+                "Found immediate operand '{}' where '{}' writes",
+                word,
+                opcode.mnemonic()
+            ));
+        }
+        parameters.push(value);
+    }
+    Ok([instruction].into_iter().chain(parameters).collect())
+}
+
+/// Assemble a program, with errors naming the line they're on.
+pub fn assemble(source: &str) -> Result<Vec<i64>, String> {
+    // The first pass finds where everything goes, so that labels can be used
+    // before they're defined.
+    let mut labels = HashMap::new();
+    let mut statements = Vec::new();
+    let mut address = 0;
+    for (idx, line) in source.lines().enumerate() {
+        let line_error = |e: String| format!("Line {}: {}", idx + 1, e);
+        let mut text = line.split(';').next().unwrap_or("").trim();
+        while let Some((name, rest)) = text.split_once(':') {
+            let name = name.trim();
+            if !is_label(name) {
+                return Err(line_error(format!("Found invalid label '{}'", name)));
+            }
+            if labels.insert(name, address).is_some() {
+                return Err(line_error(format!("Found duplicate label '{}'", name)));
+            }
+            text = rest.trim();
+        }
+        let mut words = text
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|w| !w.is_empty())
+            .peekable();
+        if let Some(Ok(expected)) = words.peek().map(|w| w.parse::<usize>()) {
+            if expected != address {
+                return Err(line_error(format!(
+                    "Expected address {} but this is address {}",
+                    expected, address
+                )));
+            }
+            words.next();
+        }
+        let statement = match words.next() {
+            Some("data") => Statement::Data(words.collect()),
+            Some(mnemonic) => match Opcode::from_mnemonic(mnemonic) {
+                Some(opcode) => Statement::Instruction(opcode, words.collect()),
+                None => {
+                    return Err(line_error(format!(
+                        "Found unknown instruction '{}'",
+                        mnemonic
+                    )))
+                }
+            },
+            None => continue,
+        };
+        address += match &statement {
+            Statement::Instruction(opcode, operands) => {
+                if operands.len() != opcode.parameter_count() {
+                    return Err(line_error(format!(
+                        "Expected {} operands for '{}' but found {}",
+                        opcode.parameter_count(),
+                        opcode.mnemonic(),
+                        operands.len()
+                    )));
+                }
+                1 + operands.len()
+            }
+            Statement::Data(values) => values.len(),
+        };
+        statements.push((idx + 1, statement));
+    }
+
+    let mut program = Vec::with_capacity(address);
+    for (line_number, statement) in statements {
+        let values = match statement {
+            Statement::Instruction(opcode, operands) => encode(opcode, &operands, &labels),
+            Statement::Data(values) => values.iter().map(|v| parse_value(v, &labels)).collect(),
+        };
+        program.extend(values.map_err(|e| format!("Line {}: {}", line_number, e))?);
+    }
+    Ok(program)
+}
+
+/// The comma-separated form that puzzle inputs use.
+pub fn format_program(program: &[i64]) -> String {
+    program
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+const COUNTDOWN: &str = "; Count down from the input to 1.
+start:  in   @count
+loop:   out  @count
+        add  @count #-1 @count
+        jnz  @count loop
+        hlt
+count:  data 0
+";
+
+#[test]
+fn test_assemble() {
+    let program = assemble(COUNTDOWN).unwrap();
+    assert_eq!(
+        format_program(&program),
+        "3,12,4,12,1001,12,-1,12,1005,12,2,99,0"
+    );
+    assert_eq!(
+        super::Intcode::new(program).run_with_inputs(&[3]),
+        Ok(vec![3, 2, 1])
+    );
+    // Relative operands, commas, and labels used as data
+    assert_eq!(
+        assemble("arb #5\nout [rb-2]\nout [rb]\nadd #1,[rb+1],@0\nend: hlt\ndata end"),
+        Ok(vec![109, 5, 204, -2, 204, 0, 2101, 1, 1, 0, 99, 10])
+    );
+}
+
+#[test]
+fn test_assemble_round_trip() {
+    for source in [
+        "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,\
+        1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,\
+        999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99",
+        "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99",
+        "109,20,21101,9,0,0,1105,1,12,99,7,8,104,5,2106,0,0",
+        "42,1,2,3,4,5,6,7,8,9,10",
+        // Modes for parameters that don't exist make these data, not code.
+        "1104,7,1099",
+        "104,7,1099,3,0",
+    ] {
+        let program = super::parse_program(source).unwrap();
+        let listing = super::disassemble::disassemble(&program).to_string();
+        assert_eq!(assemble(&listing), Ok(program), "{}", listing);
+    }
+}
+
+#[test]
+fn test_assemble_errors() {
+    for (source, error) in [
+        (
+            "add #1 #2",
+            "Line 1: Expected 3 operands for 'add' but found 2",
+        ),
+        ("nop", "Line 1: Found unknown instruction 'nop'"),
+        (
+            "hlt\nin #4",
+            "Line 2: Found immediate operand '#4' where 'in' writes",
+        ),
+        ("jnz #1 nowhere", "Line 1: Failed to find label 'nowhere'"),
+        ("a: hlt\na: hlt", "Line 2: Found duplicate label 'a'"),
+        ("3: hlt", "Line 1: Found invalid label '3'"),
+        (
+            "0 hlt\n0 hlt",
+            "Line 2: Expected address 0 but this is address 1",
+        ),
+        (
+            "out [rb+x]",
+            "Line 1: Failed to parse relative operand '[rb+x]'",
+        ),
+        ("data 1 2 x-1", "Line 1: Failed to parse value 'x-1'"),
+    ] {
+        assert_eq!(assemble(source), Err(error.to_string()));
+    }
+}
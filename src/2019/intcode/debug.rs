@@ -0,0 +1,516 @@
+//! A step debugger for Intcode programs. It's driven by text commands, so it
+//! works the same in a terminal as it does reading a script.
+
+use super::disassemble::{Listing, ListingLine};
+use super::{decode, Intcode, Opcode, Status};
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt::Write as _;
+use std::io::{BufRead, Write};
+
+/// How many steps `back` can undo.
+pub const HISTORY_LIMIT: usize = 1000;
+
+/// The most values that one `mem` command will show.
+const MEM_LIMIT: usize = 4096;
+
+const HELP: &str = "\
+step [N]            run N instructions (default 1)
+continue [N]        run until something stops the program, or for N steps
+break ADDR|OP       stop before the instruction at ADDR, or any OP (e.g. 'out')
+delete ADDR|OP      remove a breakpoint
+watch ADDR          stop when the value at ADDR changes
+unwatch ADDR        remove a watchpoint
+info                list breakpoints and watchpoints
+mem ADDR [COUNT]    show COUNT values starting at ADDR (default 8, at most 4096)
+regs                show the instruction pointer and relative base
+input VALUE...      queue input values
+back [N]            undo N steps (default 1)
+where               show the next instruction
+save FILE           save a snapshot of the machine to FILE
+load FILE           replace the machine with the snapshot in FILE
+quit                stop debugging";
+
+pub struct Debugger {
+    machine: Intcode,
+    /// The machine as it was before each of the most recent steps.
+    history: VecDeque<Intcode>,
+    breakpoints: BTreeSet<usize>,
+    opcode_breakpoints: Vec<Opcode>,
+    watchpoints: BTreeSet<usize>,
+    steps: usize,
+}
+
+enum Breakpoint {
+    Address(usize),
+    Opcode(Opcode),
+}
+
+fn parse_breakpoint(arg: Option<&str>) -> Result<Breakpoint, String> {
+    let arg = arg.ok_or("Expected an address or instruction")?;
+    match (arg.parse::<usize>(), Opcode::from_mnemonic(arg)) {
+        (Ok(address), _) => Ok(Breakpoint::Address(address)),
+        (_, Some(opcode)) => Ok(Breakpoint::Opcode(opcode)),
+        _ => Err(format!("Failed to parse breakpoint '{}'", arg)),
+    }
+}
+
+fn parse_arg<T: std::str::FromStr>(arg: Option<&str>, default: Option<T>) -> Result<T, String> {
+    match (arg, default) {
+        (Some(a), _) => a
+            .parse::<T>()
+            .map_err(|_| format!("Failed to parse argument '{}'", a)),
+        (None, Some(d)) => Ok(d),
+        (None, None) => Err("Expected an argument".to_string()),
+    }
+}
+
+impl Debugger {
+    pub fn new(machine: Intcode) -> Self {
+        Debugger {
+            machine,
+            history: VecDeque::new(),
+            breakpoints: BTreeSet::new(),
+            opcode_breakpoints: Vec::new(),
+            watchpoints: BTreeSet::new(),
+            steps: 0,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn machine(&self) -> &Intcode {
+        &self.machine
+    }
+
+    /// The next instruction, in the disassembler's format.
+    fn location(&self) -> String {
+        let ip = self.machine.ip();
+        let line = match decode(self.machine.read(ip), ip) {
+            Ok(instruction) => ListingLine::Code {
+                address: ip,
+                instruction,
+                parameters: (1..instruction.len())
+                    .map(|i| self.machine.read(ip + i))
+                    .collect(),
+            },
+            Err(_) => ListingLine::Data {
+                address: ip,
+                values: vec![self.machine.read(ip)],
+            },
+        };
+        Listing::default().format_line(&line)
+    }
+
+    fn at_breakpoint(&self) -> bool {
+        let ip = self.machine.ip();
+        self.breakpoints.contains(&ip)
+            || decode(self.machine.read(ip), ip)
+                .is_ok_and(|i| self.opcode_breakpoints.contains(&i.opcode))
+    }
+
+    /// Run one instruction, describing anything interesting in `out`. Returns
+    /// whether the program should stop here.
+    fn step_once(&mut self, out: &mut String) -> bool {
+        let watched: Vec<(usize, i64)> = self
+            .watchpoints
+            .iter()
+            .map(|a| (*a, self.machine.read(*a)))
+            .collect();
+        let before = self.machine.clone();
+        let status = match self.machine.step() {
+            Ok(s) => s,
+            Err(e) => {
+                let _ = writeln!(out, "Error: {}", e);
+                return true;
+            }
+        };
+        match status {
+            Some(Status::Halted) => {
+                let _ = writeln!(out, "Halted");
+                return true;
+            }
+            Some(Status::NeedsInput) => {
+                let _ = writeln!(out, "Waiting for input");
+                return true;
+            }
+            Some(Status::Output(value)) => {
+                let _ = writeln!(out, "Output: {}", value);
+            }
+            None => (),
+        }
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(before);
+        self.steps += 1;
+
+        let mut stop = false;
+        for (address, old) in watched {
+            let new = self.machine.read(address);
+            if new != old {
+                let _ = writeln!(out, "Watchpoint @{}: {} -> {}", address, old, new);
+                stop = true;
+            }
+        }
+        stop
+    }
+
+    /// Run a single command and return what it printed, or `None` for `quit`.
+    /// A blank command does nothing.
+    pub fn execute(&mut self, command: &str) -> Option<Result<String, String>> {
+        let mut words = command.split_whitespace();
+        let name = match words.next() {
+            Some(name) => name,
+            None => return Some(Ok(String::new())),
+        };
+        let mut out = String::new();
+        let arg = words.next();
+        let result = match name {
+            "step" | "s" => parse_arg(arg, Some(1)).map(|count: usize| {
+                for _ in 0..count {
+                    if self.step_once(&mut out) {
+                        break;
+                    }
+                }
+            }),
+            "continue" | "c" => parse_arg(arg, Some(usize::MAX)).map(|limit: usize| {
+                let mut steps = 0;
+                loop {
+                    if self.step_once(&mut out) {
+                        break;
+                    }
+                    steps += 1;
+                    if self.at_breakpoint() {
+                        let _ = writeln!(out, "Breakpoint at {}", self.machine.ip());
+                        break;
+                    }
+                    if steps == limit {
+                        let _ = writeln!(out, "Stopped after {} steps", steps);
+                        break;
+                    }
+                }
+            }),
+            "break" | "b" => parse_breakpoint(arg).map(|b| match b {
+                Breakpoint::Address(a) => {
+                    self.breakpoints.insert(a);
+                }
+                Breakpoint::Opcode(op) if !self.opcode_breakpoints.contains(&op) => {
+                    self.opcode_breakpoints.push(op)
+                }
+                Breakpoint::Opcode(_) => (),
+            }),
+            "delete" | "d" => parse_breakpoint(arg).map(|b| match b {
+                Breakpoint::Address(a) => {
+                    self.breakpoints.remove(&a);
+                }
+                Breakpoint::Opcode(op) => self.opcode_breakpoints.retain(|o| *o != op),
+            }),
+            "watch" | "w" => parse_arg(arg, None).map(|a| {
+                self.watchpoints.insert(a);
+            }),
+            "unwatch" => parse_arg(arg, None).map(|a| {
+                self.watchpoints.remove(&a);
+            }),
+            "info" | "i" => {
+                let breakpoints = self
+                    .breakpoints
+                    .iter()
+                    .map(|a| a.to_string())
+                    .chain(
+                        self.opcode_breakpoints
+                            .iter()
+                            .map(|o| o.mnemonic().to_string()),
+                    )
+                    .collect::<Vec<_>>();
+                let watchpoints = self
+                    .watchpoints
+                    .iter()
+                    .map(|a| format!("@{}", a))
+                    .collect::<Vec<_>>();
+                let list = |items: Vec<String>| {
+                    if items.is_empty() {
+                        "(none)".to_string()
+                    } else {
+                        items.join(" ")
+                    }
+                };
+                let _ = writeln!(out, "Breakpoints: {}", list(breakpoints));
+                let _ = writeln!(out, "Watchpoints: {}", list(watchpoints));
+                Ok(())
+            }
+            "mem" | "m" => parse_arg(arg, None).and_then(|start: usize| {
+                let count: usize = parse_arg(words.next(), Some(8))?;
+                let end = start
+                    .checked_add(count)
+                    .filter(|_| count <= MEM_LIMIT)
+                    .ok_or_else(|| format!("Failed to show {} values from @{}", count, start))?;
+                for row in (start..end).step_by(8) {
+                    let values: Vec<String> = (row..row.saturating_add(8).min(end))
+                        .map(|a| self.machine.read(a).to_string())
+                        .collect();
+                    let _ = writeln!(out, "@{}: {}", row, values.join(" "));
+                }
+                Ok(())
+            }),
+            "regs" | "r" => {
+                let _ = writeln!(
+                    out,
+                    "ip {}  rb {}  steps {}  inputs {}",
+                    self.machine.ip(),
+                    self.machine.relative_base(),
+                    self.steps,
+                    self.machine.pending_inputs()
+                );
+                Ok(())
+            }
+            "input" => arg
+                .into_iter()
+                .chain(words.by_ref())
+                .map(|v| parse_arg(Some(v), None))
+                .collect::<Result<Vec<i64>, _>>()
+                .map(|values| values.into_iter().for_each(|v| self.machine.push_input(v))),
+            "back" => parse_arg(arg, Some(1)).map(|count: usize| {
+                for _ in 0..count {
+                    match self.history.pop_back() {
+                        Some(machine) => {
+                            self.machine = machine;
+                            self.steps -= 1;
+                        }
+                        None => {
+                            let _ = writeln!(out, "No more history");
+                            break;
+                        }
+                    }
+                }
+            }),
+            "save" => arg
+                .ok_or_else(|| "Expected a file name".to_string())
+                .and_then(|path| {
+                    std::fs::write(path, self.machine.to_snapshot())
+                        .map_err(|e| format!("Failed to write snapshot: {}", e))
+                }),
+            "load" => arg
+                .ok_or_else(|| "Expected a file name".to_string())
+                .and_then(|path| {
+                    std::fs::read_to_string(path)
+                        .map_err(|e| format!("Failed to read snapshot: {}", e))
+                })
+                .and_then(|snapshot| Ok(Intcode::from_snapshot(&snapshot)?))
+                .map(|machine| {
+                    self.machine = machine;
+                    self.history.clear();
+                    self.steps = 0;
+                }),
+            "where" => Ok(()),
+            "help" | "h" => {
+                let _ = writeln!(out, "{}", HELP);
+                return Some(Ok(out));
+            }
+            "quit" | "q" => return None,
+            _ => Err(format!("Unknown command '{}' (try 'help')", name)),
+        };
+        // Commands that can move the program show where it ended up.
+        if matches!(
+            name,
+            "step" | "s" | "continue" | "c" | "back" | "load" | "where"
+        ) {
+            let _ = writeln!(out, "{}", self.location());
+        }
+        Some(result.map(|_| out))
+    }
+}
+
+/// Read commands until `quit` or the end of `commands`. In interactive mode
+/// there's a prompt before each command, and otherwise each command is echoed
+/// so that the output reads like a transcript.
+pub fn run_debugger(
+    debugger: &mut Debugger,
+    commands: &mut dyn BufRead,
+    out: &mut dyn Write,
+    interactive: bool,
+) -> Result<(), String> {
+    let write_error = |e: std::io::Error| format!("Failed to write debugger output: {}", e);
+    writeln!(out, "{}", debugger.location()).map_err(write_error)?;
+    loop {
+        if interactive {
+            write!(out, "(debug) ").map_err(write_error)?;
+            out.flush().map_err(write_error)?;
+        }
+        let mut command = String::new();
+        match commands.read_line(&mut command) {
+            Ok(0) => return Ok(()),
+            Ok(_) => (),
+            Err(e) => return Err(format!("Failed to read debugger command: {}", e)),
+        }
+        let command = command.trim();
+        if command.is_empty() {
+            continue;
+        }
+        if !interactive {
+            writeln!(out, "> {}", command).map_err(write_error)?;
+        }
+        match debugger.execute(command) {
+            Some(Ok(text)) => write!(out, "{}", text).map_err(write_error)?,
+            Some(Err(e)) => writeln!(out, "{}", e).map_err(write_error)?,
+            None => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+fn debug_script(program: &str, script: &str) -> String {
+    let mut debugger = Debugger::new(program.parse().unwrap());
+    let mut out = Vec::new();
+    run_debugger(&mut debugger, &mut script.as_bytes(), &mut out, false).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_debugger_stepping() {
+    let transcript = debug_script(
+        "3,11,1001,11,5,11,4,11,1105,1,0,0",
+        "regs\n\
+        step\n\
+        \n\
+        input 10 20\n\
+        step 3\n\
+        regs\n\
+        back 2\n\
+        mem 8 6\n\
+        mem 18446744073709551615 8\n\
+        mem 18446744073709551614 1\n\
+        mem 0 18446744073709551614\n\
+        mem 0 4097\n\
+        bogus\n\
+        quit\n\
+        regs\n",
+    );
+    let expected = "     0  in   @11\n\
+        > regs\n\
+        ip 0  rb 0  steps 0  inputs 0\n\
+        > step\n\
+        Waiting for input\n\
+        \x20    0  in   @11\n\
+        > input 10 20\n\
+        > step 3\n\
+        Output: 15\n\
+        \x20    8  jnz  #1 #0\n\
+        > regs\n\
+        ip 8  rb 0  steps 3  inputs 1\n\
+        > back 2\n\
+        \x20    2  add  @11 #5 @11\n\
+        > mem 8 6\n\
+        @8: 1105 1 0 10 0 0\n\
+        > mem 18446744073709551615 8\n\
+        Failed to show 8 values from @18446744073709551615\n\
+        > mem 18446744073709551614 1\n\
+        @18446744073709551614: 0\n\
+        > mem 0 18446744073709551614\n\
+        Failed to show 18446744073709551614 values from @0\n\
+        > mem 0 4097\n\
+        Failed to show 4097 values from @0\n\
+        > bogus\n\
+        Unknown command 'bogus' (try 'help')\n\
+        > quit\n";
+    assert_eq!(transcript, expected);
+
+    // Blank lines are skipped rather than ending the session.
+    let mut debugger = Debugger::new("99".parse().unwrap());
+    assert_eq!(debugger.execute("  "), Some(Ok(String::new())));
+}
+
+#[test]
+fn test_debugger_breakpoints() {
+    // Count up from 0, forever.
+    let program = "1001,9,1,9,4,9,1105,1,0,0";
+    let transcript = debug_script(
+        program,
+        "break 6\n\
+        continue\n\
+        delete 6\n\
+        break out\n\
+        c\n\
+        c\n\
+        delete out\n\
+        watch 9\n\
+        continue\n\
+        unwatch 9\n\
+        info\n\
+        continue 10\n\
+        back 1001\n",
+    );
+    let expected = "     0  add  @9 #1 @9\n\
+        > break 6\n\
+        > continue\n\
+        Output: 1\n\
+        Breakpoint at 6\n\
+        \x20    6  jnz  #1 #0\n\
+        > delete 6\n\
+        > break out\n\
+        > c\n\
+        Breakpoint at 4\n\
+        \x20    4  out  @9\n\
+        > c\n\
+        Output: 2\n\
+        Breakpoint at 4\n\
+        \x20    4  out  @9\n\
+        > delete out\n\
+        > watch 9\n\
+        > continue\n\
+        Output: 3\n\
+        Watchpoint @9: 3 -> 4\n\
+        \x20    4  out  @9\n\
+        > unwatch 9\n\
+        > info\n\
+        Breakpoints: (none)\n\
+        Watchpoints: (none)\n\
+        > continue 10\n\
+        Output: 4\n\
+        Output: 5\n\
+        Output: 6\n\
+        Output: 7\n\
+        Stopped after 10 steps\n\
+        \x20    6  jnz  #1 #0\n\
+        > back 1001\n\
+        No more history\n\
+        \x20    0  add  @9 #1 @9\n";
+    assert_eq!(transcript, expected);
+
+    // History only goes back so far.
+    let mut debugger = Debugger::new(program.parse().unwrap());
+    let _ = debugger.execute(&format!("step {}", HISTORY_LIMIT + 30));
+    let _ = debugger.execute(&format!("back {}", HISTORY_LIMIT + 30));
+    assert_eq!(debugger.machine().read(9), 10);
+}
+
+#[test]
+fn test_debugger_snapshots() {
+    let path = std::env::temp_dir().join(format!("intcode-debug-{}.snapshot", std::process::id()));
+    let path = path.to_str().unwrap();
+    let transcript = debug_script(
+        "3,11,1001,11,5,11,4,11,1105,1,0,0",
+        &format!(
+            "input 1\nstep 2\nsave {0}\ncontinue\nload {0}\nregs\nload {0}.missing\n",
+            path
+        ),
+    );
+    std::fs::remove_file(path).unwrap();
+    let expected = format!(
+        "     0  in   @11\n\
+        > input 1\n\
+        > step 2\n\
+        \x20    6  out  @11\n\
+        > save {0}\n\
+        > continue\n\
+        Output: 6\n\
+        Waiting for input\n\
+        \x20    0  in   @11\n\
+        > load {0}\n\
+        \x20    6  out  @11\n\
+        > regs\n\
+        ip 6  rb 0  steps 0  inputs 0\n\
+        > load {0}.missing\n",
+        path
+    );
+    assert!(transcript.starts_with(&expected), "{}", transcript);
+    assert!(transcript[expected.len()..].starts_with("Failed to read snapshot"));
+}
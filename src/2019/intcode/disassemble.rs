@@ -0,0 +1,304 @@
+//! Turn Intcode programs back into something a person can read.
+//!
+//! Each instruction is listed with its address, mnemonic and operands, where
+//! `@120` reads address 120, `#5` is the value 5, and `[rb+3]` is three past
+//! the relative base. Jump targets get labels like `L12`, which stand for the
+//! label's address as an immediate value.
+
+use super::{decode, Instruction, Mode, Opcode};
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+/// How many values go on each line of data.
+const DATA_PER_LINE: usize = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ListingLine {
+    Code {
+        address: usize,
+        instruction: Instruction,
+        parameters: Vec<i64>,
+    },
+    Data {
+        address: usize,
+        values: Vec<i64>,
+    },
+}
+
+impl ListingLine {
+    pub fn address(&self) -> usize {
+        match self {
+            ListingLine::Code { address, .. } | ListingLine::Data { address, .. } => *address,
+        }
+    }
+
+    /// The number of memory cells that the line covers.
+    pub fn len(&self) -> usize {
+        match self {
+            ListingLine::Code { instruction, .. } => instruction.len(),
+            ListingLine::Data { values, .. } => values.len(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Listing {
+    pub lines: Vec<ListingLine>,
+    /// The addresses that jumps go to.
+    pub labels: BTreeSet<usize>,
+}
+
+pub fn label(address: usize) -> String {
+    format!("L{}", address)
+}
+
+impl Listing {
+    fn format_operand(&self, mode: Mode, value: i64, jump_target: bool) -> String {
+        match mode {
+            Mode::Position => format!("@{}", value),
+            Mode::Immediate => match usize::try_from(value) {
+                Ok(a) if jump_target && self.labels.contains(&a) => label(a),
+                _ => format!("#{}", value),
+            },
+            Mode::Relative => format!("[rb{:+}]", value),
+        }
+    }
+
+    /// One line of the listing, without its label.
+    pub fn format_line(&self, line: &ListingLine) -> String {
+        let (address, mnemonic, operands) = match line {
+            ListingLine::Code {
+                address,
+                instruction,
+                parameters,
+            } => {
+                let is_jump =
+                    matches!(instruction.opcode, Opcode::JumpIfTrue | Opcode::JumpIfFalse);
+                let operands: Vec<String> = parameters
+                    .iter()
+                    .zip(instruction.modes)
+                    .enumerate()
+                    .map(|(idx, (value, mode))| {
+                        self.format_operand(mode, *value, is_jump && idx == 1)
+                    })
+                    .collect();
+                (address, instruction.opcode.mnemonic(), operands)
+            }
+            ListingLine::Data { address, values } => (
+                address,
+                "data",
+                values.iter().map(|v| v.to_string()).collect(),
+            ),
+        };
+        format!("{:>6}  {:<4} {}", address, mnemonic, operands.join(" "))
+            .trim_end()
+            .to_string()
+    }
+}
+
+impl Listing {
+    /// The whole listing, with a column of `width` characters in front of
+    /// each line holding whatever `annotation` says about it.
+    pub fn annotate(&self, width: usize, annotation: impl Fn(&ListingLine) -> String) -> String {
+        let mut text = String::new();
+        for line in &self.lines {
+            let (note, blank) = match width {
+                0 => (String::new(), String::new()),
+                w => (format!("{:>w$} ", annotation(line)), " ".repeat(w + 1)),
+            };
+            if self.labels.contains(&line.address()) {
+                text += &format!("{}{}:\n", blank, label(line.address()));
+            }
+            text += &format!("{}{}\n", note, self.format_line(line));
+        }
+        text
+    }
+}
+
+impl Display for Listing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.annotate(0, |_| String::new()))
+    }
+}
+
+/// Find the instructions in a program, indexed by the address they start at.
+///
+/// There's no way to know for sure which values are code, so this guesses:
+/// code is whatever can be reached from address 0 by running on to the next
+/// instruction or following a jump to an immediate address. Jumps to computed
+/// addresses can't be followed, but a function call usually stores its return
+/// address as a constant first, so a constant that points just past an
+/// unconditional jump is taken to be code as well, along with anything in
+/// `known`. Everything else is data.
+fn find_code(program: &[i64], known: &[usize]) -> Vec<Option<Instruction>> {
+    let mut starts = vec![None; program.len()];
+    let mut covered = vec![false; program.len()];
+    let mut after_jumps = BTreeSet::new();
+    let mut constants = BTreeSet::new();
+    // Addresses that are known to be code go last, so that they're looked at
+    // first.
+    let mut entries: Vec<usize> = [0].into_iter().chain(known.iter().copied()).collect();
+    while !entries.is_empty() {
+        while let Some(mut address) = entries.pop() {
+            while address < program.len() {
+                let instruction = match decode(program[address], address) {
+                    Ok(i) if address + i.len() <= program.len() => i,
+                    _ => break,
+                };
+                let end = address + instruction.len();
+                if covered[address..end].contains(&true) {
+                    break;
+                }
+                covered[address..end].iter_mut().for_each(|c| *c = true);
+                starts[address] = Some(instruction);
+
+                let parameters = &program[address + 1..end];
+                let immediates = parameters
+                    .iter()
+                    .zip(instruction.modes)
+                    .filter(|(_, mode)| *mode == Mode::Immediate)
+                    .filter_map(|(value, _)| usize::try_from(*value).ok());
+                match instruction.opcode {
+                    Opcode::Halt => break,
+                    Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                        if instruction.modes[1] == Mode::Immediate {
+                            entries.extend(usize::try_from(parameters[1]));
+                        }
+                        let always = instruction.modes[0] == Mode::Immediate
+                            && (parameters[0] != 0) == (instruction.opcode == Opcode::JumpIfTrue);
+                        if always {
+                            after_jumps.insert(end);
+                            break;
+                        }
+                    }
+                    Opcode::Add | Opcode::Multiply => constants.extend(immediates),
+                    _ => (),
+                }
+                address = end;
+            }
+        }
+        entries = constants
+            .intersection(&after_jumps)
+            .filter(|a| !covered[**a])
+            .copied()
+            .collect();
+    }
+    starts
+}
+
+pub fn disassemble(program: &[i64]) -> Listing {
+    disassemble_with_code(program, &[])
+}
+
+/// Like [`disassemble`], but with some addresses that are known to hold
+/// instructions, such as the ones that ran when the program was profiled.
+pub fn disassemble_with_code(program: &[i64], known: &[usize]) -> Listing {
+    let starts = find_code(program, known);
+    let mut lines = Vec::new();
+    let mut address = 0;
+    while address < program.len() {
+        let line = match starts[address] {
+            Some(instruction) => ListingLine::Code {
+                address,
+                instruction,
+                parameters: program[address + 1..address + instruction.len()].to_vec(),
+            },
+            None => {
+                let values = program[address..]
+                    .iter()
+                    .zip(&starts[address..])
+                    .take_while(|(_, start)| start.is_none())
+                    .take(DATA_PER_LINE)
+                    .map(|(value, _)| *value)
+                    .collect();
+                ListingLine::Data { address, values }
+            }
+        };
+        address += line.len();
+        lines.push(line);
+    }
+
+    let labels = lines
+        .iter()
+        .filter_map(|line| match line {
+            ListingLine::Code {
+                instruction,
+                parameters,
+                ..
+            } if matches!(instruction.opcode, Opcode::JumpIfTrue | Opcode::JumpIfFalse)
+                && instruction.modes[1] == Mode::Immediate =>
+            {
+                usize::try_from(parameters[1]).ok()
+            }
+            _ => None,
+        })
+        .filter(|target| starts.get(*target).is_some_and(|s| s.is_some()))
+        .collect();
+    Listing { lines, labels }
+}
+
+#[test]
+fn test_disassemble() {
+    let program = super::parse_program(
+        "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,\
+        1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,\
+        999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99",
+    )
+    .unwrap();
+    let expected = "     0  in   @21\n\
+        \x20    2  eq   @21 #8 @20\n\
+        \x20    6  jnz  @20 L22\n\
+        \x20    9  lt   #8 @21 @20\n\
+        \x20   13  jz   @20 L31\n\
+        \x20   16  jz   #0 L36\n\
+        \x20   19  data 98 0 0\n\
+        L22:\n\
+        \x20   22  mul  @21 #125 @20\n\
+        \x20   26  out  @20\n\
+        \x20   28  jnz  #1 L46\n\
+        L31:\n\
+        \x20   31  out  #999\n\
+        \x20   33  jnz  #1 L46\n\
+        L36:\n\
+        \x20   36  add  #1000 #1 @20\n\
+        \x20   40  out  @20\n\
+        \x20   42  jnz  #1 L46\n\
+        \x20   45  data 98\n\
+        L46:\n\
+        \x20   46  hlt\n";
+    assert_eq!(disassemble(&program).to_string(), expected);
+}
+
+#[test]
+fn test_disassemble_call() {
+    // Store a return address on the stack, call a function that outputs 5,
+    // and return to the halt. The values after the halt are data.
+    let program = [
+        109, 20, 21101, 9, 0, 0, 1105, 1, 12, 99, 7, 8, 104, 5, 2106, 0, 0,
+    ];
+    let listing = disassemble(&program);
+    let expected = "     0  arb  #20\n\
+        \x20    2  add  #9 #0 [rb+0]\n\
+        \x20    6  jnz  #1 L12\n\
+        \x20    9  hlt\n\
+        \x20   10  data 7 8\n\
+        L12:\n\
+        \x20   12  out  #5\n\
+        \x20   14  jz   #0 [rb+0]\n";
+    assert_eq!(listing.to_string(), expected);
+    assert_eq!(
+        super::Intcode::new(program.to_vec()).run_with_inputs(&[]),
+        Ok(vec![5])
+    );
+
+    // Nothing decodes, and long runs of data are split up.
+    let listing = disassemble(&[42; 10]);
+    assert_eq!(listing.lines.len(), 2);
+    assert_eq!(
+        listing.lines[1],
+        ListingLine::Data {
+            address: 8,
+            values: vec![42, 42]
+        }
+    );
+}
@@ -0,0 +1,494 @@
+//! A faster way to run Intcode, which decodes each instruction once and keeps
+//! the result until something writes over it.
+//!
+//! Decoded instructions have their operands resolved as far as they can be
+//! ahead of time, and a few common idioms get their own operations: `add x #0
+//! y` and `mul x #1 y` are copies, and jumps on a constant condition either
+//! always or never jump. Anything unusual is left to the reference
+//! interpreter in [`Intcode::step`], so the two always agree.
+
+use super::memory::PAGE_SIZE;
+use super::{decode, Intcode, IntcodeError, Mode, Opcode, Status, MAX_MEMORY};
+
+/// The longest instruction, so that a write knows which cached instructions
+/// it might have changed.
+const MAX_INSTRUCTION_LEN: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operand {
+    Immediate(i64),
+    Position(usize),
+    Relative(i64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    Add(Operand, Operand, Operand),
+    Multiply(Operand, Operand, Operand),
+    LessThan(Operand, Operand, Operand),
+    Equals(Operand, Operand, Operand),
+    Copy(Operand, Operand),
+    Input(Operand),
+    Output(Operand),
+    JumpIfTrue(Operand, Operand),
+    JumpIfFalse(Operand, Operand),
+    Jump(Operand),
+    /// A jump whose condition is never met. The target still gets read,
+    /// since reading it could fail.
+    NoJump(Operand),
+    AdjustRelativeBase(Operand),
+    Halt,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Decoded {
+    op: Op,
+    len: usize,
+}
+
+/// Decoded instructions, indexed by address. Like [`super::memory::Memory`],
+/// it's split into pages that are only allocated once something in them is
+/// decoded, so that code far out in memory doesn't need a huge table.
+#[derive(Clone, Debug, Default)]
+struct DecodeCache {
+    pages: Vec<Option<Box<[Option<Decoded>]>>>,
+}
+
+impl DecodeCache {
+    fn get(&self, address: usize) -> Option<Decoded> {
+        self.pages.get(address / PAGE_SIZE)?.as_ref()?[address % PAGE_SIZE]
+    }
+
+    fn insert(&mut self, address: usize, decoded: Decoded) {
+        let page_idx = address / PAGE_SIZE;
+        if page_idx >= self.pages.len() {
+            self.pages.resize_with(page_idx + 1, || None);
+        }
+        let page = self.pages[page_idx].get_or_insert_with(|| vec![None; PAGE_SIZE].into());
+        page[address % PAGE_SIZE] = Some(decoded);
+    }
+
+    /// Forget the instructions at `first..last`.
+    fn forget(&mut self, first: usize, last: usize) {
+        for address in first..last {
+            if let Some(Some(page)) = self.pages.get_mut(address / PAGE_SIZE) {
+                page[address % PAGE_SIZE] = None;
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.pages.clear();
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FastIntcode {
+    machine: Intcode,
+    cache: DecodeCache,
+}
+
+impl FastIntcode {
+    pub fn new(machine: Intcode) -> Self {
+        FastIntcode {
+            machine,
+            cache: DecodeCache::default(),
+        }
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.machine.push_input(value);
+    }
+
+    #[cfg(test)]
+    pub fn read(&self, address: usize) -> i64 {
+        self.machine.read(address)
+    }
+
+    /// Write memory, forgetting any decoded instruction that the write
+    /// touched.
+    pub fn write(&mut self, address: usize, value: i64) {
+        self.machine.memory.write(address, value);
+        let first = address.saturating_sub(MAX_INSTRUCTION_LEN - 1);
+        self.cache.forget(first, address + 1);
+    }
+
+    /// Decode the instruction at `ip`, or return `None` if it's one for the
+    /// reference interpreter.
+    fn decode_at(&self, ip: usize) -> Option<Decoded> {
+        let instruction = decode(self.machine.read(ip), ip).ok()?;
+        let mut operands = [Operand::Immediate(0); 3];
+        for (idx, operand) in operands
+            .iter_mut()
+            .take(instruction.opcode.parameter_count())
+            .enumerate()
+        {
+            let raw = self.machine.read(ip + 1 + idx);
+            *operand = match instruction.modes[idx] {
+                Mode::Immediate => Operand::Immediate(raw),
+                Mode::Relative => Operand::Relative(raw),
+                // Bad addresses are an error whenever the instruction runs.
+                Mode::Position => match usize::try_from(raw) {
+                    Ok(a) if a < MAX_MEMORY => Operand::Position(a),
+                    _ => return None,
+                },
+            };
+        }
+        let [a, b, c] = operands;
+        let op = match instruction.opcode {
+            Opcode::Add if b == Operand::Immediate(0) => Op::Copy(a, c),
+            Opcode::Add if a == Operand::Immediate(0) => Op::Copy(b, c),
+            Opcode::Multiply if b == Operand::Immediate(1) => Op::Copy(a, c),
+            Opcode::Multiply if a == Operand::Immediate(1) => Op::Copy(b, c),
+            Opcode::Add => Op::Add(a, b, c),
+            Opcode::Multiply => Op::Multiply(a, b, c),
+            Opcode::LessThan => Op::LessThan(a, b, c),
+            Opcode::Equals => Op::Equals(a, b, c),
+            Opcode::Input => Op::Input(a),
+            Opcode::Output => Op::Output(a),
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => match a {
+                Operand::Immediate(condition) => {
+                    if (condition != 0) == (instruction.opcode == Opcode::JumpIfTrue) {
+                        Op::Jump(b)
+                    } else {
+                        Op::NoJump(b)
+                    }
+                }
+                _ if instruction.opcode == Opcode::JumpIfTrue => Op::JumpIfTrue(a, b),
+                _ => Op::JumpIfFalse(a, b),
+            },
+            Opcode::AdjustRelativeBase => Op::AdjustRelativeBase(a),
+            Opcode::Halt => Op::Halt,
+        };
+        Some(Decoded {
+            op,
+            len: instruction.len(),
+        })
+    }
+
+    fn invalid_address(&self, target: i64) -> IntcodeError {
+        IntcodeError::InvalidAddress {
+            address: self.machine.ip,
+            target,
+        }
+    }
+
+    fn address(&self, operand: Operand) -> Result<usize, IntcodeError> {
+        match operand {
+            Operand::Position(a) => Ok(a),
+            Operand::Relative(offset) => {
+                let target = self.machine.relative_base.wrapping_add(offset);
+                match usize::try_from(target) {
+                    Ok(a) if a < MAX_MEMORY => Ok(a),
+                    _ => Err(self.invalid_address(target)),
+                }
+            }
+            // Decoding already rejected immediate write parameters.
+            Operand::Immediate(_) => unreachable!(),
+        }
+    }
+
+    fn value(&self, operand: Operand) -> Result<i64, IntcodeError> {
+        match operand {
+            Operand::Immediate(v) => Ok(v),
+            _ => Ok(self.machine.memory.read(self.address(operand)?)),
+        }
+    }
+
+    fn jump_target(&self, target: i64) -> Result<usize, IntcodeError> {
+        usize::try_from(target).map_err(|_| self.invalid_address(target))
+    }
+
+    /// Run a single instruction, just like [`Intcode::step`].
+    pub fn step(&mut self) -> Result<Option<Status>, IntcodeError> {
+        let ip = self.machine.ip;
+        let decoded = match self.cache.get(ip) {
+            Some(d) => d,
+            None => match self.decode_at(ip) {
+                Some(d) => {
+                    self.cache.insert(ip, d);
+                    d
+                }
+                None => {
+                    // The reference interpreter might write anywhere, so
+                    // nothing in the cache can be trusted afterwards.
+                    self.cache.clear();
+                    return self.machine.step();
+                }
+            },
+        };
+        let mut next_ip = ip + decoded.len;
+        let mut status = None;
+        match decoded.op {
+            Op::Add(a, b, c) => {
+                let value = self.value(a)?.wrapping_add(self.value(b)?);
+                let address = self.address(c)?;
+                self.write(address, value);
+            }
+            Op::Multiply(a, b, c) => {
+                let value = self.value(a)?.wrapping_mul(self.value(b)?);
+                let address = self.address(c)?;
+                self.write(address, value);
+            }
+            Op::LessThan(a, b, c) => {
+                let value = (self.value(a)? < self.value(b)?) as i64;
+                let address = self.address(c)?;
+                self.write(address, value);
+            }
+            Op::Equals(a, b, c) => {
+                let value = (self.value(a)? == self.value(b)?) as i64;
+                let address = self.address(c)?;
+                self.write(address, value);
+            }
+            Op::Copy(a, c) => {
+                let value = self.value(a)?;
+                let address = self.address(c)?;
+                self.write(address, value);
+            }
+            Op::Input(a) => {
+                let address = self.address(a)?;
+                match self.machine.inputs.pop_front() {
+                    Some(value) => self.write(address, value),
+                    None => return Ok(Some(Status::NeedsInput)),
+                }
+            }
+            Op::Output(a) => status = Some(Status::Output(self.value(a)?)),
+            Op::JumpIfTrue(a, b) => {
+                let (condition, target) = (self.value(a)?, self.value(b)?);
+                if condition != 0 {
+                    next_ip = self.jump_target(target)?;
+                }
+            }
+            Op::JumpIfFalse(a, b) => {
+                let (condition, target) = (self.value(a)?, self.value(b)?);
+                if condition == 0 {
+                    next_ip = self.jump_target(target)?;
+                }
+            }
+            Op::Jump(b) => next_ip = self.jump_target(self.value(b)?)?,
+            Op::NoJump(b) => {
+                self.value(b)?;
+            }
+            Op::AdjustRelativeBase(a) => {
+                self.machine.relative_base =
+                    self.machine.relative_base.wrapping_add(self.value(a)?);
+            }
+            Op::Halt => return Ok(Some(Status::Halted)),
+        }
+        self.machine.ip = next_ip;
+        Ok(status)
+    }
+
+    /// Run until the program halts, produces output, or needs input.
+    pub fn run(&mut self) -> Result<Status, IntcodeError> {
+        loop {
+            if let Some(status) = self.step()? {
+                return Ok(status);
+            }
+        }
+    }
+
+    /// Run until the program halts, feeding it `inputs` and collecting all of
+    /// its output.
+    pub fn run_with_inputs(&mut self, inputs: &[i64]) -> Result<Vec<i64>, IntcodeError> {
+        inputs.iter().for_each(|v| self.push_input(*v));
+        let mut outputs = Vec::new();
+        loop {
+            match self.run()? {
+                Status::Output(value) => outputs.push(value),
+                Status::Halted => return Ok(outputs),
+                Status::NeedsInput => {
+                    return Err(IntcodeError::MissingInput {
+                        address: self.machine.ip,
+                    })
+                }
+            }
+        }
+    }
+}
+
+/// A small random number generator, so that the differential tests don't
+/// need a dependency and always see the same programs.
+#[cfg(test)]
+struct Lcg(u64);
+
+#[cfg(test)]
+impl Lcg {
+    fn next(&mut self, bound: i64) -> i64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % bound as u64) as i64
+    }
+
+    /// A program made mostly of valid instructions whose operands point back
+    /// into the program, so that it jumps around and rewrites itself.
+    fn program(&mut self, len: usize) -> Vec<i64> {
+        (0..len)
+            .map(|_| match self.next(10) {
+                0..=3 => {
+                    let opcode = [1, 2, 3, 4, 5, 6, 7, 8, 9, 99][self.next(10) as usize];
+                    opcode + 100 * self.next(3) + 1000 * self.next(3) + 10000 * self.next(3)
+                }
+                4..=8 => self.next(len as i64 + 2) - 1,
+                _ => self.next(2001) - 1000,
+            })
+            .collect()
+    }
+}
+
+/// Run both interpreters a step at a time, checking that they agree on every
+/// step.
+#[cfg(test)]
+fn check_same_steps(program: Vec<i64>, inputs: &[i64], max_steps: usize) {
+    let mut reference = Intcode::new(program.clone());
+    let mut fast = FastIntcode::new(Intcode::new(program.clone()));
+    inputs.iter().for_each(|v| {
+        reference.push_input(*v);
+        fast.push_input(*v);
+    });
+    for _ in 0..max_steps {
+        let expected = reference.step();
+        assert_eq!(fast.step(), expected, "program {:?}", program);
+        assert_eq!(fast.machine.ip, reference.ip);
+        assert_eq!(fast.machine.relative_base, reference.relative_base);
+        assert_eq!(fast.machine.memory(), reference.memory());
+        if !matches!(expected, Ok(None | Some(Status::Output(_)))) {
+            return;
+        }
+    }
+}
+
+#[test]
+fn test_matches_reference_on_examples() {
+    let larger = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,\
+        1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,\
+        999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+    for (program, inputs) in [
+        (larger, vec![7]),
+        (larger, vec![8]),
+        (larger, vec![9]),
+        (
+            "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99",
+            vec![],
+        ),
+        ("1102,34915192,34915192,7,4,7,99,0", vec![]),
+        ("1,9,10,3,2,3,11,0,99,30,40,50", vec![]),
+        ("3,0,99", vec![]),
+        ("1105,1,-1", vec![]),
+        ("1106,0,5,99,99,1105,1,-4", vec![]),
+        ("1106,1,-4,99", vec![]),
+        ("1106,1,2201,99", vec![]),
+        ("109,-5,1206,0,0,99", vec![]),
+    ] {
+        let program = super::parse_program(program).unwrap();
+        check_same_steps(program.clone(), &inputs, 10_000);
+        assert_eq!(
+            FastIntcode::new(Intcode::new(program.clone())).run_with_inputs(&inputs),
+            Intcode::new(program).run_with_inputs(&inputs)
+        );
+    }
+}
+
+#[test]
+fn test_self_modifying_code() {
+    // The add rewrites the operand of the output after it has been decoded,
+    // and then rewrites the output into a halt.
+    let program = super::assemble::assemble(
+        "loop:   out  #1
+                add  @1 #1 @1
+                eq   @1 #4 @flag
+                jz   @flag loop
+                add  #99 #0 @0
+                jz   #0 loop
+        flag:   data 0",
+    )
+    .unwrap();
+    let mut fast = FastIntcode::new(Intcode::new(program.clone()));
+    assert_eq!(fast.run_with_inputs(&[]), Ok(vec![1, 2, 3]));
+    assert_eq!(fast.read(0), 99);
+    check_same_steps(program, &[], 100);
+
+    // Writes from outside count as well.
+    let mut fast = FastIntcode::new("104,5,99".parse().unwrap());
+    assert_eq!(fast.run(), Ok(Status::Output(5)));
+    fast.write(1, 6);
+    fast.machine.ip = 0;
+    assert_eq!(fast.run(), Ok(Status::Output(6)));
+}
+
+#[test]
+fn test_code_in_high_memory() {
+    // Write "out #7, hlt" just below the end of memory and jump to it.
+    let high = MAX_MEMORY - 3;
+    let program = super::parse_program(&format!(
+        "1101,104,0,{0},1101,7,0,{1},1101,99,0,{2},1105,1,{0}",
+        high,
+        high + 1,
+        high + 2
+    ))
+    .unwrap();
+    let mut reference = Intcode::new(program.clone());
+    let mut fast = FastIntcode::new(Intcode::new(program));
+    let output = fast.run_with_inputs(&[]);
+    assert_eq!(output, Ok(vec![7]));
+    assert_eq!(output, reference.run_with_inputs(&[]));
+    assert_eq!(fast.machine.ip, reference.ip);
+    assert_eq!(fast.read(high), reference.read(high));
+    // Only the pages holding decoded instructions are allocated.
+    assert_eq!(fast.cache.pages.iter().flatten().count(), 2);
+}
+
+#[test]
+fn test_matches_reference_on_random_programs() {
+    let mut rng = Lcg(2019);
+    for _ in 0..2000 {
+        let len = 8 + rng.next(56) as usize;
+        let program = rng.program(len);
+        let inputs: Vec<i64> = (0..rng.next(5)).map(|_| rng.next(len as i64)).collect();
+        check_same_steps(program, &inputs, 500);
+    }
+}
+
+/// Compare the two interpreters on a program that runs for a long time. Run
+/// it with `cargo test --release -- --ignored --nocapture bench`.
+#[test]
+#[ignore]
+fn bench_fast_interpreter() {
+    // Sum the numbers below the input with a nested loop, one at a time.
+    let program = super::assemble::assemble(
+        "        in   @n
+        outer:  add  @i #1 @i
+                add  #0 #0 @j
+        inner:  add  @sum #1 @sum
+                add  @j #1 @j
+                lt   @j @i @flag
+                jnz  @flag inner
+                lt   @i @n @flag
+                jnz  @flag outer
+                out  @sum
+                hlt
+        n:      data 0
+        i:      data 0
+        j:      data 0
+        sum:    data 0
+        flag:   data 0",
+    )
+    .unwrap();
+    let n = 5000;
+    let start = std::time::Instant::now();
+    let expected = Intcode::new(program.clone()).run_with_inputs(&[n]);
+    let reference_time = start.elapsed();
+    let start = std::time::Instant::now();
+    let output = FastIntcode::new(Intcode::new(program)).run_with_inputs(&[n]);
+    let fast_time = start.elapsed();
+    assert_eq!(output, expected);
+    assert_eq!(output, Ok(vec![n * (n + 1) / 2]));
+    println!(
+        "reference: {:?}, fast: {:?} ({:.1}x)",
+        reference_time,
+        fast_time,
+        reference_time.as_secs_f64() / fast_time.as_secs_f64()
+    );
+}
@@ -0,0 +1,104 @@
+//! Intcode memory, split into pages that clones of a machine share until one
+//! of them writes.
+
+use std::sync::Arc;
+
+/// The number of values in each page.
+pub const PAGE_SIZE: usize = 1024;
+
+#[derive(Clone, Debug, Default)]
+pub struct Memory {
+    pages: Vec<Arc<Vec<i64>>>,
+    /// One past the highest address that has been written (or loaded).
+    len: usize,
+}
+
+impl From<Vec<i64>> for Memory {
+    fn from(program: Vec<i64>) -> Self {
+        let pages = program
+            .chunks(PAGE_SIZE)
+            .map(|chunk| {
+                let mut page = chunk.to_vec();
+                page.resize(PAGE_SIZE, 0);
+                Arc::new(page)
+            })
+            .collect();
+        Memory {
+            pages,
+            len: program.len(),
+        }
+    }
+}
+
+impl Memory {
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Addresses that have never been written hold 0.
+    pub fn read(&self, address: usize) -> i64 {
+        match self.pages.get(address / PAGE_SIZE) {
+            Some(page) => page[address % PAGE_SIZE],
+            None => 0,
+        }
+    }
+
+    /// Write a value, copying its page first if another clone still uses it.
+    pub fn write(&mut self, address: usize, value: i64) {
+        let page_idx = address / PAGE_SIZE;
+        if page_idx >= self.pages.len() {
+            // New pages all start out as the same page of zeros.
+            let zeros = Arc::new(vec![0; PAGE_SIZE]);
+            self.pages.resize(page_idx + 1, zeros);
+        }
+        Arc::make_mut(&mut self.pages[page_idx])[address % PAGE_SIZE] = value;
+        self.len = self.len.max(address + 1);
+    }
+
+    pub fn to_vec(&self) -> Vec<i64> {
+        self.pages
+            .iter()
+            .flat_map(|p| p.iter())
+            .take(self.len)
+            .copied()
+            .collect()
+    }
+
+    /// How many pages this memory has in common with `other`.
+    #[cfg(test)]
+    pub fn shared_pages(&self, other: &Memory) -> usize {
+        self.pages
+            .iter()
+            .zip(&other.pages)
+            .filter(|(a, b)| Arc::ptr_eq(a, b))
+            .count()
+    }
+}
+
+#[test]
+fn test_copy_on_write() {
+    let mut memory = Memory::from((0..3000).collect::<Vec<i64>>());
+    assert_eq!(memory.len(), 3000);
+    assert_eq!(memory.read(2999), 2999);
+    assert_eq!(memory.read(3000), 0);
+    assert_eq!(memory.read(1 << 40), 0);
+
+    let mut fork = memory.clone();
+    assert_eq!(fork.shared_pages(&memory), 3);
+    fork.write(1500, -1);
+    assert_eq!(fork.shared_pages(&memory), 2);
+    assert_eq!((memory.read(1500), fork.read(1500)), (1500, -1));
+
+    // Growing past the end adds pages without touching the shared ones.
+    fork.write(10 * PAGE_SIZE + 5, 7);
+    assert_eq!(fork.len(), 10 * PAGE_SIZE + 6);
+    assert_eq!(fork.shared_pages(&memory), 2);
+    assert_eq!(fork.read(10 * PAGE_SIZE + 5), 7);
+    assert_eq!(fork.read(9 * PAGE_SIZE), 0);
+
+    memory.write(0, 42);
+    assert_eq!(memory.to_vec()[..3], [42, 1, 2]);
+    assert_eq!(fork.to_vec()[..3], [0, 1, 2]);
+    assert_eq!(fork.to_vec().len(), fork.len());
+}
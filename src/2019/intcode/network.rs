@@ -0,0 +1,296 @@
+//! Several Intcode machines wired together so that each one's output is the
+//! next one's input.
+
+use super::{Intcode, IntcodeError, IntcodeInput, IntcodeOutput, Status};
+use std::collections::VecDeque;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// How long a threaded machine waits for input before checking whether the
+/// whole network is stuck.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topology {
+    /// Each machine's output goes to the next one, and the last machine's
+    /// output is the result.
+    Pipeline,
+    /// Like a pipeline, except that the last machine's output also goes back
+    /// to the first one.
+    Ring,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scheduling {
+    /// Take turns on the current thread, running each machine until it needs
+    /// input.
+    Cooperative,
+    /// Give each machine its own thread, connected by channels.
+    #[allow(dead_code)]
+    Threaded,
+}
+
+/// Connect `machines` and run them until they've all halted. `inputs` go to
+/// the first machine, after anything already queued on it (such as a phase
+/// setting). Returns the last value that the last machine output, if any.
+pub fn run_network(
+    machines: Vec<Intcode>,
+    inputs: &[i64],
+    topology: Topology,
+    scheduling: Scheduling,
+) -> Result<Option<i64>, IntcodeError> {
+    if machines.is_empty() {
+        return Ok(None);
+    }
+    match scheduling {
+        Scheduling::Cooperative => run_cooperative(machines, inputs, topology),
+        Scheduling::Threaded => run_threaded(machines, inputs, topology),
+    }
+}
+
+fn run_cooperative(
+    mut machines: Vec<Intcode>,
+    inputs: &[i64],
+    topology: Topology,
+) -> Result<Option<i64>, IntcodeError> {
+    let count = machines.len();
+    let mut queues = vec![VecDeque::new(); count];
+    queues[0].extend(inputs);
+    let mut halted = vec![false; count];
+    let mut last_output = None;
+    while halted.contains(&false) {
+        // Without new output or a machine halting, the next round would see
+        // exactly the same (empty) queues as this one.
+        let mut progress = false;
+        for idx in 0..count {
+            if halted[idx] {
+                continue;
+            }
+            let mut input = std::mem::take(&mut queues[idx]);
+            let mut output = Vec::new();
+            halted[idx] = machines[idx].run_with(&mut input, &mut output)? == Status::Halted;
+            progress |= halted[idx] || !output.is_empty();
+            if idx == count - 1 {
+                last_output = output.last().copied().or(last_output);
+                if topology == Topology::Pipeline {
+                    continue;
+                }
+            }
+            queues[(idx + 1) % count].extend(output);
+        }
+        if !progress {
+            return Err(IntcodeError::Deadlock);
+        }
+    }
+    Ok(last_output)
+}
+
+/// What the threads in a network know about each other, so that they can tell
+/// when they're all stuck.
+struct Tracker {
+    running: Vec<bool>,
+    waiting: Vec<bool>,
+    /// How many values have been sent to each machine but not read yet.
+    queued: Vec<usize>,
+    deadlocked: bool,
+}
+
+impl Tracker {
+    /// Whether every machine that's still running is waiting for input, and
+    /// there's nothing on the way to any of them.
+    fn is_stuck(&self) -> bool {
+        (0..self.running.len())
+            .all(|i| !self.running[i] || (self.waiting[i] && self.queued[i] == 0))
+    }
+}
+
+struct NetworkInput<'a> {
+    idx: usize,
+    receiver: Receiver<i64>,
+    tracker: &'a Mutex<Tracker>,
+}
+
+impl IntcodeInput for NetworkInput<'_> {
+    fn next_input(&mut self) -> Option<i64> {
+        loop {
+            match self.receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(value) => {
+                    let mut tracker = self.tracker.lock().unwrap();
+                    tracker.queued[self.idx] -= 1;
+                    tracker.waiting[self.idx] = false;
+                    return Some(value);
+                }
+                Err(RecvTimeoutError::Disconnected) => return None,
+                Err(RecvTimeoutError::Timeout) => {
+                    let mut tracker = self.tracker.lock().unwrap();
+                    tracker.waiting[self.idx] = true;
+                    if tracker.deadlocked || tracker.is_stuck() {
+                        tracker.deadlocked = true;
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct NetworkOutput<'a> {
+    /// The machine to send output to, if any.
+    next: Option<(usize, Sender<i64>)>,
+    last: Option<i64>,
+    tracker: &'a Mutex<Tracker>,
+}
+
+impl IntcodeOutput for NetworkOutput<'_> {
+    fn write_output(&mut self, value: i64) {
+        self.last = Some(value);
+        if let Some((idx, sender)) = &self.next {
+            // Count the value before sending it, so that the receiver can't
+            // read it first.
+            let mut tracker = self.tracker.lock().unwrap();
+            tracker.queued[*idx] += 1;
+            if sender.send(value).is_err() {
+                tracker.queued[*idx] -= 1;
+            }
+        }
+    }
+}
+
+fn run_threaded(
+    machines: Vec<Intcode>,
+    inputs: &[i64],
+    topology: Topology,
+) -> Result<Option<i64>, IntcodeError> {
+    let count = machines.len();
+    let (senders, receivers): (Vec<Sender<i64>>, Vec<Receiver<i64>>) =
+        (0..count).map(|_| channel()).unzip();
+    inputs.iter().for_each(|v| senders[0].send(*v).unwrap());
+    let tracker = Mutex::new(Tracker {
+        running: vec![true; count],
+        waiting: vec![false; count],
+        queued: vec![0; count],
+        deadlocked: false,
+    });
+    tracker.lock().unwrap().queued[0] = inputs.len();
+
+    let tracker = &tracker;
+    let results: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = machines
+            .into_iter()
+            .zip(receivers)
+            .enumerate()
+            .map(|(idx, (mut machine, receiver))| {
+                let next = match (idx + 1 < count, topology) {
+                    (true, _) => Some((idx + 1, senders[idx + 1].clone())),
+                    (false, Topology::Ring) => Some((0, senders[0].clone())),
+                    (false, Topology::Pipeline) => None,
+                };
+                scope.spawn(move || {
+                    let mut input = NetworkInput {
+                        idx,
+                        receiver,
+                        tracker,
+                    };
+                    let mut output = NetworkOutput {
+                        next,
+                        last: None,
+                        tracker,
+                    };
+                    let result = machine.run_with(&mut input, &mut output);
+                    tracker.lock().unwrap().running[idx] = false;
+                    result.map(|status| (status, output.last))
+                })
+            })
+            .collect();
+        // Only the machines hold senders now, so a machine's input closes once
+        // the one before it stops.
+        drop(senders);
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    // A machine that failed may have left the others waiting, so its error
+    // is the one to report.
+    let results = results.into_iter().collect::<Result<Vec<_>, _>>()?;
+    if results
+        .iter()
+        .any(|(status, _)| *status == Status::NeedsInput)
+    {
+        return Err(IntcodeError::Deadlock);
+    }
+    Ok(results[count - 1].1)
+}
+
+#[cfg(test)]
+fn amplifiers(program: &str, phases: &[i64]) -> Vec<Intcode> {
+    phases
+        .iter()
+        .map(|phase| {
+            let mut amplifier: Intcode = program.parse().unwrap();
+            amplifier.push_input(*phase);
+            amplifier
+        })
+        .collect()
+}
+
+#[test]
+fn test_pipeline() {
+    let program = "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0";
+    for scheduling in [Scheduling::Cooperative, Scheduling::Threaded] {
+        let machines = amplifiers(program, &[4, 3, 2, 1, 0]);
+        assert_eq!(
+            run_network(machines, &[0], Topology::Pipeline, scheduling),
+            Ok(Some(43210))
+        );
+    }
+    assert_eq!(
+        run_network(
+            Vec::new(),
+            &[0],
+            Topology::Pipeline,
+            Scheduling::Cooperative
+        ),
+        Ok(None)
+    );
+}
+
+#[test]
+fn test_ring() {
+    let program = "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,\
+        27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+    for scheduling in [Scheduling::Cooperative, Scheduling::Threaded] {
+        let machines = amplifiers(program, &[9, 8, 7, 6, 5]);
+        assert_eq!(
+            run_network(machines, &[0], Topology::Ring, scheduling),
+            Ok(Some(139629729))
+        );
+    }
+}
+
+#[test]
+fn test_network_errors() {
+    for scheduling in [Scheduling::Cooperative, Scheduling::Threaded] {
+        // Echo machines in a ring with nothing to pass around
+        let machines = vec!["3,0,4,0,99".parse().unwrap(); 3];
+        assert_eq!(
+            run_network(machines, &[], Topology::Ring, scheduling),
+            Err(IntcodeError::Deadlock)
+        );
+        // The second machine wants two values but only gets one.
+        let machines = vec!["3,0,4,0,99".parse().unwrap(), "3,0,3,0,99".parse().unwrap()];
+        assert_eq!(
+            run_network(machines, &[1], Topology::Pipeline, scheduling),
+            Err(IntcodeError::Deadlock)
+        );
+        // Errors from any machine stop the network.
+        let machines = vec!["3,0,4,0,99".parse().unwrap(), "42".parse().unwrap()];
+        assert_eq!(
+            run_network(machines, &[1], Topology::Ring, scheduling),
+            Err(IntcodeError::InvalidOpcode {
+                address: 0,
+                opcode: 42
+            })
+        );
+    }
+}
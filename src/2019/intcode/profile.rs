@@ -0,0 +1,330 @@
+//! Count where an Intcode program spends its time.
+
+use super::disassemble::{disassemble_with_code, label, ListingLine};
+use super::{decode, Intcode, IntcodeError, Opcode, Status};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+
+/// How many entries each section of the report lists.
+const REPORT_LENGTH: usize = 10;
+
+/// How wide the count column of a heat listing is.
+const HEAT_WIDTH: usize = 10;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlockStats {
+    pub entries: u64,
+    pub instructions: u64,
+    /// The address of the last instruction that ran in the block.
+    pub end: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    pub instructions: u64,
+    pub by_opcode: HashMap<Opcode, u64>,
+    pub by_address: Vec<u64>,
+    /// Jumps to the same address or an earlier one, which is what loops are
+    /// made of, keyed by where they jump from and to.
+    pub back_edges: HashMap<(usize, usize), u64>,
+    /// Addresses that a jump has landed on.
+    pub jump_targets: HashSet<usize>,
+    /// Addresses of the jumps and halts that have run, which end blocks.
+    pub block_ends: HashSet<usize>,
+}
+
+/// Sort counts from most to least, breaking ties by key.
+fn ranked<K: Copy + Ord, V: Copy>(
+    counts: impl Iterator<Item = (K, V)>,
+    count: impl Fn(V) -> u64,
+) -> Vec<(K, V)> {
+    let mut ranked: Vec<(K, V)> = counts.collect();
+    ranked.sort_by_key(|(k, v)| (std::cmp::Reverse(count(*v)), *k));
+    ranked.truncate(REPORT_LENGTH);
+    ranked
+}
+
+impl Profile {
+    /// Runs of instructions that are always entered at the top, keyed by the
+    /// address they start at. A block starts wherever a jump has landed and
+    /// just after a jump (whether or not it jumped), so blocks never overlap.
+    pub fn blocks(&self) -> BTreeMap<usize, BlockStats> {
+        let mut blocks: BTreeMap<usize, BlockStats> = BTreeMap::new();
+        let mut previous: Option<usize> = None;
+        let executed = (0..self.by_address.len()).filter(|a| self.by_address[*a] > 0);
+        for address in executed {
+            let count = self.by_address[address];
+            let starts_block = match previous {
+                Some(p) => self.block_ends.contains(&p) || self.jump_targets.contains(&address),
+                None => true,
+            };
+            if starts_block {
+                blocks.insert(
+                    address,
+                    BlockStats {
+                        entries: count,
+                        instructions: 0,
+                        end: address,
+                    },
+                );
+            }
+            let (_, block) = blocks.iter_mut().next_back().unwrap();
+            block.instructions += count;
+            block.end = address;
+            previous = Some(address);
+        }
+        blocks
+    }
+
+    fn percent(&self, count: u64) -> f64 {
+        100.0 * count as f64 / self.instructions.max(1) as f64
+    }
+
+    /// The busiest opcodes, blocks and back-edges.
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        let _ = writeln!(report, "Executed {} instructions", self.instructions);
+
+        let _ = writeln!(report, "\nInstructions by opcode:");
+        let opcodes = self
+            .by_opcode
+            .iter()
+            .map(|(op, count)| (Opcode::ALL.iter().position(|o| o == op), (*op, *count)));
+        for (_, (opcode, count)) in ranked(opcodes, |(_, c)| c) {
+            let _ = writeln!(
+                report,
+                "  {:<4} {:>12} {:>6.1}%",
+                opcode.mnemonic(),
+                count,
+                self.percent(count)
+            );
+        }
+
+        let _ = writeln!(report, "\nHottest blocks:");
+        for (start, stats) in ranked(self.blocks().into_iter(), |s| s.instructions) {
+            let _ = writeln!(
+                report,
+                "  {:>6}-{:<6} entered {:>10} {:>12} instructions {:>6.1}%",
+                start,
+                stats.end,
+                stats.entries,
+                stats.instructions,
+                self.percent(stats.instructions)
+            );
+        }
+
+        let _ = writeln!(report, "\nHottest back-edges:");
+        for ((from, to), count) in ranked(self.back_edges.iter().map(|(e, c)| (*e, *c)), |c| c) {
+            let _ = writeln!(report, "  {:>6} -> {:<8} {:>12}", from, label(to), count);
+        }
+        report
+    }
+
+    /// A disassembly of `program` with the number of times each instruction
+    /// ran beside it.
+    pub fn heat_listing(&self, program: &[i64]) -> String {
+        let executed: Vec<usize> = (0..self.by_address.len())
+            .filter(|a| self.by_address[*a] > 0)
+            .collect();
+        disassemble_with_code(program, &executed).annotate(HEAT_WIDTH, |line| match line {
+            ListingLine::Code { address, .. } => match self.by_address.get(*address) {
+                Some(count) if *count > 0 => count.to_string(),
+                _ => "-".to_string(),
+            },
+            ListingLine::Data { .. } => String::new(),
+        })
+    }
+}
+
+/// Runs a machine with the reference interpreter, counting everything that it
+/// does.
+pub struct Profiler {
+    machine: Intcode,
+    profile: Profile,
+}
+
+impl Profiler {
+    pub fn new(machine: Intcode) -> Self {
+        Profiler {
+            machine,
+            profile: Profile::default(),
+        }
+    }
+
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.machine.push_input(value);
+    }
+
+    /// Run a single instruction, just like [`Intcode::step`].
+    pub fn step(&mut self) -> Result<Option<Status>, IntcodeError> {
+        let ip = self.machine.ip();
+        let instruction = decode(self.machine.read(ip), ip);
+        let status = self.machine.step()?;
+        let instruction = match instruction {
+            // Waiting for input doesn't run anything.
+            Ok(i) if status != Some(Status::NeedsInput) => i,
+            _ => return Ok(status),
+        };
+
+        let profile = &mut self.profile;
+        profile.instructions += 1;
+        *profile.by_opcode.entry(instruction.opcode).or_insert(0) += 1;
+        if ip >= profile.by_address.len() {
+            profile.by_address.resize(ip + 1, 0);
+        }
+        profile.by_address[ip] += 1;
+
+        match instruction.opcode {
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                profile.block_ends.insert(ip);
+                let target = self.machine.ip();
+                if target != ip + instruction.len() {
+                    profile.jump_targets.insert(target);
+                }
+                if target <= ip {
+                    *profile.back_edges.entry((ip, target)).or_insert(0) += 1;
+                }
+            }
+            Opcode::Halt => {
+                profile.block_ends.insert(ip);
+            }
+            _ => (),
+        }
+        Ok(status)
+    }
+
+    /// Run until the program halts, feeding it `inputs` and collecting all of
+    /// its output.
+    pub fn run_with_inputs(&mut self, inputs: &[i64]) -> Result<Vec<i64>, IntcodeError> {
+        inputs.iter().for_each(|v| self.push_input(*v));
+        let mut outputs = Vec::new();
+        loop {
+            match self.step()? {
+                None => (),
+                Some(Status::Output(value)) => outputs.push(value),
+                Some(Status::Halted) => return Ok(outputs),
+                Some(Status::NeedsInput) => {
+                    return Err(IntcodeError::MissingInput {
+                        address: self.machine.ip(),
+                    })
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+const NESTED_LOOPS: &str = "
+        in   @n
+outer:  add  @i #1 @i
+        add  #0 #0 @j
+inner:  add  @j #1 @j
+        lt   @j @i @flag
+        jnz  @flag inner
+        lt   @i @n @flag
+        jnz  @flag outer
+        out  @i
+        hlt
+n:      data 0
+i:      data 0
+j:      data 0
+flag:   data 0";
+
+#[test]
+fn test_profile_counts() {
+    let program = super::assemble::assemble(NESTED_LOOPS).unwrap();
+    let mut profiler = Profiler::new(Intcode::new(program));
+    assert_eq!(profiler.run_with_inputs(&[3]), Ok(vec![3]));
+    let profile = profiler.profile();
+
+    assert_eq!(profile.instructions, 33);
+    let opcodes = [
+        (Opcode::Input, 1),
+        (Opcode::Add, 12),
+        (Opcode::LessThan, 9),
+        (Opcode::JumpIfTrue, 9),
+        (Opcode::Output, 1),
+        (Opcode::Halt, 1),
+    ];
+    assert_eq!(profile.by_opcode, opcodes.into_iter().collect());
+    assert_eq!(
+        profile.by_address,
+        vec![
+            1, 0, 3, 0, 0, 0, 3, 0, 0, 0, 6, 0, 0, 0, 6, 0, 0, 0, 6, 0, 0, 3, 0, 0, 0, 3, 0, 0, 1,
+            0, 1
+        ]
+    );
+    assert_eq!(
+        profile.back_edges,
+        [((18, 10), 3), ((25, 2), 2)].into_iter().collect()
+    );
+    let block = |entries, instructions, end| BlockStats {
+        entries,
+        instructions,
+        end,
+    };
+    let blocks = profile.blocks();
+    assert_eq!(
+        blocks,
+        [
+            (0, block(1, 1, 0)),
+            (2, block(3, 6, 6)),
+            (10, block(6, 18, 18)),
+            (21, block(3, 6, 25)),
+            (28, block(1, 2, 30)),
+        ]
+        .into_iter()
+        .collect()
+    );
+    let total: u64 = blocks.values().map(|b| b.instructions).sum();
+    assert_eq!(total, profile.instructions);
+}
+
+#[test]
+fn test_profile_report() {
+    let program = super::assemble::assemble(NESTED_LOOPS).unwrap();
+    let mut profiler = Profiler::new(Intcode::new(program.clone()));
+    profiler.run_with_inputs(&[3]).unwrap();
+    let report = profiler.profile().report();
+    let expected = "Executed 33 instructions\n\
+        \n\
+        Instructions by opcode:\n\
+        \x20 add            12   36.4%\n\
+        \x20 jnz             9   27.3%\n\
+        \x20 lt              9   27.3%\n\
+        \x20 in              1    3.0%\n\
+        \x20 out             1    3.0%\n\
+        \x20 hlt             1    3.0%\n\
+        \n\
+        Hottest blocks:\n\
+        \x20     10-18     entered          6           18 instructions   54.5%\n\
+        \x20      2-6      entered          3            6 instructions   18.2%\n\
+        \x20     21-25     entered          3            6 instructions   18.2%\n\
+        \x20     28-30     entered          1            2 instructions    6.1%\n\
+        \x20      0-0      entered          1            1 instructions    3.0%\n\
+        \n\
+        Hottest back-edges:\n\
+        \x20     18 -> L10                 3\n\
+        \x20     25 -> L2                  2\n";
+    assert_eq!(report, expected);
+
+    let listing = profiler.profile().heat_listing(&program);
+    let expected = "         1      0  in   @31\n\
+        \x20          L2:\n\
+        \x20        3      2  add  @32 #1 @32\n\
+        \x20        3      6  add  #0 #0 @33\n\
+        \x20          L10:\n\
+        \x20        6     10  add  @33 #1 @33\n\
+        \x20        6     14  lt   @33 @32 @34\n\
+        \x20        6     18  jnz  @34 L10\n\
+        \x20        3     21  lt   @32 @31 @34\n\
+        \x20        3     25  jnz  @34 L2\n\
+        \x20        1     28  out  @32\n\
+        \x20        1     30  hlt\n\
+        \x20              31  data 0 0 0 0\n";
+    assert_eq!(listing, expected);
+}
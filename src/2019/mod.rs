@@ -1 +1,16 @@
-mod intcode;
+pub mod intcode;
+
+#[path = "02.rs"]
+pub mod day_02;
+
+#[path = "05.rs"]
+pub mod day_05;
+
+#[path = "07.rs"]
+pub mod day_07;
+
+#[path = "08.rs"]
+pub mod day_08;
+
+#[path = "09.rs"]
+pub mod day_09;
@@ -1,29 +1,121 @@
-use crate::util::{parse_u64, AdventResult, AdventSolution};
+use crate::util::bigint::BigUint;
+use crate::util::{parse_u64, AdventResult, AdventSolution, Params};
 
-pub fn part_1(input: &str) -> AdventResult {
-    let values = parse_u64(input);
-    for a in values.iter() {
-        for b in values.iter() {
-            if a + b == 2020 {
-                return Ok(AdventSolution::from(a * b));
-            }
-        }
+/// Find `k` distinct entries of `values` that add up to `target`, returning
+/// their indices in increasing order of value.
+///
+/// The values are sorted once, which takes O(n log n). Then the smallest
+/// remaining value is fixed in turn until two are left, and those are found
+/// by walking two pointers in from either end. That makes the search
+/// O(n^(k-1)) for k >= 2 (and O(log n) for k = 1), using O(n) extra memory.
+/// So pairs among a million entries cost one linear pass after sorting, and
+/// triples among ten thousand cost at most about 5 * 10^7 pointer steps. A
+/// search usually stops much earlier than that, because values that already
+/// overshoot the target end it.
+pub fn k_sum(values: &[u64], k: usize, target: u64) -> Option<Vec<usize>> {
+    if k > values.len() {
+        return None;
+    }
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by_key(|idx| values[*idx]);
+    let sorted: Vec<u64> = order.iter().map(|idx| values[*idx]).collect();
+    let mut chosen = Vec::with_capacity(k);
+    if k_sum_sorted(&sorted, 0, k, target, &mut chosen) {
+        Some(chosen.iter().map(|pos| order[*pos]).collect())
+    } else {
+        None
     }
-    Err(String::from("Failed to find solution"))
 }
 
-pub fn part_2(input: &str) -> AdventResult {
-    let values = parse_u64(input);
-    for a in values.iter() {
-        for b in values.iter() {
-            for c in values.iter() {
-                if a + b + c == 2020 {
-                    return Ok(AdventSolution::from(a * b * c));
+/// Search `sorted[start..]` for `k` entries that add up to `target`, pushing
+/// their positions onto `chosen`.
+fn k_sum_sorted(
+    sorted: &[u64],
+    start: usize,
+    k: usize,
+    target: u64,
+    chosen: &mut Vec<usize>,
+) -> bool {
+    let remaining = &sorted[start.min(sorted.len())..];
+    match k {
+        0 => target == 0,
+        1 => match remaining.binary_search(&target) {
+            Ok(pos) => {
+                chosen.push(start + pos);
+                true
+            }
+            Err(_) => false,
+        },
+        2 => {
+            if remaining.len() < 2 {
+                return false;
+            }
+            let (mut low, mut high) = (0, remaining.len() - 1);
+            while low < high {
+                let sum = remaining[low] as u128 + remaining[high] as u128;
+                match sum.cmp(&(target as u128)) {
+                    std::cmp::Ordering::Less => low += 1,
+                    std::cmp::Ordering::Greater => high -= 1,
+                    std::cmp::Ordering::Equal => {
+                        chosen.extend([start + low, start + high]);
+                        return true;
+                    }
                 }
             }
+            false
         }
+        _ => {
+            for (offset, value) in remaining.iter().enumerate() {
+                // Every later entry is at least this big, so once k of them
+                // overshoot there's nothing left to find.
+                if *value as u128 * k as u128 > target as u128 {
+                    break;
+                }
+                // A repeated value can't lead anywhere the first copy didn't.
+                if offset > 0 && remaining[offset - 1] == *value {
+                    continue;
+                }
+                chosen.push(start + offset);
+                if k_sum_sorted(sorted, start + offset + 1, k - 1, target - value, chosen) {
+                    return true;
+                }
+                chosen.pop();
+            }
+            false
+        }
+    }
+}
+
+/// Multiply together `k` distinct entries that add up to the target. Both can
+/// be changed with the `k` and `target` parameters, and the product is exact
+/// even when it doesn't fit in a `u64`.
+fn find_entries(input: &str, default_k: usize, params: &Params) -> AdventResult {
+    let values = parse_u64(input);
+    let k = params.get("k", default_k)?;
+    let target = params.get("target", 2020)?;
+    if k > values.len() {
+        return Err(format!(
+            "Failed to choose {} distinct entries from {}",
+            k,
+            values.len()
+        ));
+    }
+    match k_sum(&values, k, target) {
+        Some(indices) => Ok(AdventSolution::from(
+            indices.iter().fold(BigUint::one(), |product, idx| {
+                &product * &BigUint::from(values[*idx])
+            }),
+        )),
+        None => Err(String::from("Failed to find solution")),
     }
-    Err(String::from("Failed to find solution"))
+}
+
+pub fn part_1_with_params(input: &str, params: &Params) -> AdventResult {
+    find_entries(input, 2, params)
+}
+
+pub fn part_2_with_params(input: &str, params: &Params) -> AdventResult {
+    find_entries(input, 3, params)
 }
 
 #[cfg(test)]
@@ -39,10 +131,74 @@ use crate::util::check_solution;
 
 #[test]
 fn test_part_1() {
-    check_solution(&DAY_01_SAMPLE_INPUT.to_string(), 514579, &part_1);
+    check_solution(DAY_01_SAMPLE_INPUT, 514579, &|input| {
+        part_1_with_params(input, &Params::default())
+    });
 }
 
 #[test]
 fn test_part_2() {
-    check_solution(&DAY_01_SAMPLE_INPUT.to_string(), 241861950, &part_2);
+    check_solution(DAY_01_SAMPLE_INPUT, 241861950, &|input| {
+        part_2_with_params(input, &Params::default())
+    });
+}
+
+#[test]
+fn test_entries_are_distinct() {
+    // 1010 + 1010 is 2020, but there's only one 1010 to use.
+    let part_1 = |input: &str| part_1_with_params(input, &Params::default());
+    assert!(part_1("1010\n5\n").is_err());
+    check_solution("1010\n5\n1010\n", 1_020_100, &part_1);
+}
+
+#[test]
+fn test_k_sum() {
+    let values = parse_u64(DAY_01_SAMPLE_INPUT);
+    assert_eq!(k_sum(&values, 2, 2020), Some(vec![3, 0]));
+    assert_eq!(k_sum(&values, 3, 2020), Some(vec![2, 4, 1]));
+    assert_eq!(k_sum(&values, 1, 979), Some(vec![1]));
+    assert_eq!(k_sum(&values, 0, 0), Some(vec![]));
+    assert_eq!(k_sum(&values, 2, 1), None);
+    assert_eq!(k_sum(&values, 7, 2020), None);
+    assert_eq!(k_sum(&values, usize::MAX, 2020), None);
+    let indices = k_sum(&values, 4, 366 + 299 + 675 + 1456).unwrap();
+    let mut sorted = indices.clone();
+    sorted.sort();
+    assert_eq!(sorted, vec![2, 3, 4, 5]);
+    assert_eq!(k_sum(&[u64::MAX, 1, u64::MAX], 2, u64::MAX), None);
+
+    // Larger inputs, with the only answer at the very end of the search
+    let values: Vec<u64> = (0..100_000).map(|v| 1_000 + v * 2).chain([1, 3]).collect();
+    assert_eq!(k_sum(&values, 2, 4), Some(vec![100_000, 100_001]));
+    let values: Vec<u64> = (0..2_000).map(|v| 1_000 + v * 4).chain([1, 2, 3]).collect();
+    let mut indices = k_sum(&values, 3, 6).unwrap();
+    indices.sort();
+    assert_eq!(indices, vec![2_000, 2_001, 2_002]);
+}
+
+#[test]
+fn test_params() {
+    let params = Params::parse(["k=4", "target=2796"].into_iter()).unwrap();
+    check_solution(DAY_01_SAMPLE_INPUT, 366 * 299 * 675 * 1456_u64, &|input| {
+        part_1_with_params(input, &params)
+    });
+    let params = Params::parse(["k=two"].into_iter()).unwrap();
+    assert!(part_1_with_params(DAY_01_SAMPLE_INPUT, &params).is_err());
+    // More entries than the input has can't be chosen, however large k is.
+    for k in ["7", "4000000000", "18446744073709551615"] {
+        let param = format!("k={}", k);
+        let params = Params::parse([param.as_str()].into_iter()).unwrap();
+        assert_eq!(
+            part_1_with_params(DAY_01_SAMPLE_INPUT, &params),
+            Err(format!("Failed to choose {} distinct entries from 6", k))
+        );
+    }
+
+    // The product of big entries overflows a u64.
+    let big = 1_u64 << 40;
+    let input = format!("{}\n{}\n7\n", big, big + 1);
+    let target = format!("target={}", 2 * big + 1);
+    let params = Params::parse([target.as_str()].into_iter()).unwrap();
+    let product = &BigUint::from(big) * &BigUint::from(big + 1);
+    check_solution(&input, product, &|input| part_1_with_params(input, &params));
 }
@@ -1,13 +1,19 @@
+use std::io::BufRead;
+
 use regex::Regex;
 
-use crate::util::{AdventResult, AdventSolution};
+use crate::util::{read_lines, AdventResult, AdventSolution};
 
-pub fn part_1(input: &str) -> AdventResult {
+pub fn part_1(input: &mut dyn BufRead) -> AdventResult {
     let mut valid_count = 0;
     let re =
         Regex::new(r"(?P<lower>\d+)-(?P<upper>\d+) (?P<key>[a-z]): (?P<password>[a-z]+)").unwrap();
-    for line in input.lines() {
-        let caps = re.captures(line).unwrap();
+    for line in read_lines(input) {
+        let line = line?;
+        let caps = match re.captures(&line) {
+            Some(c) => c,
+            None => return Err(format!("Failed to parse password entry '{}'", line)),
+        };
         let ((lower_bound, upper_bound), key, password) = (
             (
                 caps["lower"].parse::<u8>().unwrap(),
@@ -29,12 +35,16 @@ pub fn part_1(input: &str) -> AdventResult {
     Ok(AdventSolution::from(valid_count))
 }
 
-pub fn part_2(input: &str) -> AdventResult {
+pub fn part_2(input: &mut dyn BufRead) -> AdventResult {
     let mut valid_count = 0;
     let re =
         Regex::new(r"(?P<pos1>\d+)-(?P<pos2>\d+) (?P<key>[a-z]): (?P<password>[a-z]+)").unwrap();
-    for line in input.lines() {
-        let caps = re.captures(line).unwrap();
+    for line in read_lines(input) {
+        let line = line?;
+        let caps = match re.captures(&line) {
+            Some(c) => c,
+            None => return Err(format!("Failed to parse password entry '{}'", line)),
+        };
         let ((pos1, pos2), key, password) = (
             (
                 (caps["pos1"].parse::<u8>().unwrap() - 1) as usize,
@@ -44,7 +54,7 @@ pub fn part_2(input: &str) -> AdventResult {
             caps["password"].to_string(),
         );
         if password.len() < pos2 + 1 {
-            return Err(format!("Entry is too short: '{}'", input));
+            return Err(format!("Entry is too short: '{}'", line));
         }
         let pos1_char = password.chars().nth(pos1).unwrap();
         let pos2_char = password.chars().nth(pos2).unwrap();
@@ -61,14 +71,14 @@ const DAY_02_SAMPLE_INPUT: &str = "1-3 a: abcde\n\
     2-9 c: ccccccccc\n";
 
 #[cfg(test)]
-use crate::util::check_solution;
+use crate::util::check_streaming_solution;
 
 #[test]
 fn test_part_1() {
-    check_solution(&DAY_02_SAMPLE_INPUT.to_string(), 2, &part_1);
+    check_streaming_solution(DAY_02_SAMPLE_INPUT, 2, &part_1);
 }
 
 #[test]
 fn test_part_2() {
-    check_solution(&DAY_02_SAMPLE_INPUT.to_string(), 1, &part_2);
+    check_streaming_solution(DAY_02_SAMPLE_INPUT, 1, &part_2);
 }
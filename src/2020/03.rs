@@ -54,10 +54,10 @@ use crate::util::check_solution;
 
 #[test]
 fn test_part_1() {
-    check_solution(&DAY_03_SAMPLE_INPUT.to_string(), 7, &part_1);
+    check_solution(DAY_03_SAMPLE_INPUT, 7, &part_1);
 }
 
 #[test]
 fn test_part_2() {
-    check_solution(&DAY_03_SAMPLE_INPUT.to_string(), 336, &part_2);
+    check_solution(DAY_03_SAMPLE_INPUT, 336, &part_2);
 }
@@ -1,39 +1,31 @@
+use crate::util::number_theory::{discrete_log, mod_pow};
 use crate::util::{parse_u64, AdventResult, AdventSolution};
 
+const MODULUS: u64 = 20201227;
+
 fn transform_subject_number(subject_number: u64, loop_size: u64) -> u64 {
-    let mut result = 1;
-    for _ in 1..=loop_size {
-        result = (result * subject_number) % 20201227;
+    mod_pow(subject_number, loop_size, MODULUS).unwrap()
+}
+
+fn find_loop_size(public_key: u64) -> Result<u64, String> {
+    match discrete_log(7, public_key, MODULUS) {
+        Some(l) => Ok(l),
+        None => Err(format!("Failed to find loop size for '{}'", public_key)),
     }
-    result
 }
 
 pub fn part_1(input: &str) -> AdventResult {
     let public_keys = parse_u64(input);
     let (card_pubkey, door_pubkey) = (public_keys[0], public_keys[1]);
 
-    // try lots of loop sizes
-    // stop when we've found both pubkeys somewhere in the output
-    let mut test_loop_size = 1;
-    let (mut card_loop_size, mut door_loop_size): (Option<u64>, Option<u64>) = (None, None);
-    let mut test_pubkey = 1;
-    while card_loop_size.is_none() || door_loop_size.is_none() {
-        test_pubkey = (test_pubkey * 7) % 20201227;
-        if test_pubkey == card_pubkey {
-            // we found the card's loop size
-            card_loop_size = Some(test_loop_size);
-        }
-        if test_pubkey == door_pubkey {
-            // we found the door's loop size
-            door_loop_size = Some(test_loop_size);
-        }
-        test_loop_size += 1;
-    }
+    // the loop size is the discrete logarithm of the pubkey
+    let (card_loop_size, door_loop_size) =
+        (find_loop_size(card_pubkey)?, find_loop_size(door_pubkey)?);
     // use the loop sizes to compute the encryption key
-    let encryption_key = transform_subject_number(card_pubkey, door_loop_size.unwrap());
+    let encryption_key = transform_subject_number(card_pubkey, door_loop_size);
     // there are two ways to compute the encryption key, and they must match
     assert_eq!(
-        transform_subject_number(door_pubkey, card_loop_size.unwrap()),
+        transform_subject_number(door_pubkey, card_loop_size),
         encryption_key
     );
     Ok(AdventSolution::from(encryption_key))
@@ -1,43 +1,41 @@
-use crate::util::{parse_u64, AdventResult, AdventSolution};
+use std::io::BufRead;
 
-pub fn part_1(input: &str) -> AdventResult {
-    let depths = parse_u64(input);
-    if depths.len() < 2 {
-        return Err(format!(
-            "Not enough depth measurements: found {}",
-            depths.len()
-        ));
-    }
+use crate::util::{read_lines, AdventResult, AdventSolution};
+
+/// Count the depths that are deeper than the one `window` measurements before.
+///
+/// Comparing sums of sliding windows is the same as comparing the depths that
+/// enter and leave the window, since the rest of it is shared. Only the last
+/// `window` depths are kept.
+fn count_increases(input: &mut dyn BufRead, window: usize) -> AdventResult {
+    let mut recent = vec![0; window];
+    let mut count = 0;
     let mut increase_count = 0;
-    let mut last_depth = depths[0];
-    for depth in depths[1..].iter() {
-        if *depth > last_depth {
+    for line in read_lines(input) {
+        let line = line?;
+        let depth = match line.parse::<u64>() {
+            Ok(d) => d,
+            Err(e) => return Err(format!("Failed to parse depth '{}': {}", line, e)),
+        };
+        let slot = count % window;
+        if count >= window && depth > recent[slot] {
             increase_count += 1;
         }
-        last_depth = *depth;
+        recent[slot] = depth;
+        count += 1;
+    }
+    if count <= window {
+        return Err(format!("Not enough depth measurements: found {}", count));
     }
     Ok(AdventSolution::from(increase_count))
 }
 
-pub fn part_2(input: &str) -> AdventResult {
-    let depths = parse_u64(input);
-    if depths.len() < 4 {
-        return Err(format!(
-            "Not enough depth measurements: found {}",
-            depths.len()
-        ));
-    }
-    let mut increase_count = 0;
-    let mut last_measurement = depths[0] + depths[1] + depths[2];
-    let mut new_measurement: u64;
-    for i in 3..depths.len() {
-        new_measurement = last_measurement - depths[i - 3] + depths[i];
-        if new_measurement > last_measurement {
-            increase_count += 1;
-        }
-        last_measurement = new_measurement;
-    }
-    Ok(AdventSolution::from(increase_count))
+pub fn part_1(input: &mut dyn BufRead) -> AdventResult {
+    count_increases(input, 1)
+}
+
+pub fn part_2(input: &mut dyn BufRead) -> AdventResult {
+    count_increases(input, 3)
 }
 
 #[cfg(test)]
@@ -53,14 +51,16 @@ const DAY_01_SAMPLE_INPUT: &str = "199\n\
         263\n";
 
 #[cfg(test)]
-use crate::util::check_solution;
+use crate::util::check_streaming_solution;
 
 #[test]
 fn test_part_1() {
-    check_solution(&DAY_01_SAMPLE_INPUT.to_string(), 7, &part_1);
+    check_streaming_solution(DAY_01_SAMPLE_INPUT, 7, &part_1);
+    assert!(part_1(&mut "199\n".as_bytes()).is_err());
 }
 
 #[test]
 fn test_part_2() {
-    check_solution(&DAY_01_SAMPLE_INPUT.to_string(), 5, &part_2);
+    check_streaming_solution(DAY_01_SAMPLE_INPUT, 5, &part_2);
+    assert!(part_2(&mut "199\n200\n208\n".as_bytes()).is_err());
 }
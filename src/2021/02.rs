@@ -1,43 +1,77 @@
-use crate::util::{AdventResult, AdventSolution};
-
-pub fn part_1(input: &str) -> AdventResult {
-    let mut horizontal_position = 0;
-    let mut depth = 0;
-    for line in input.lines() {
-        let (direction, amount) = {
-            let s: Vec<&str> = line.split(' ').collect();
-            (s[0], s[1].parse::<u64>().unwrap())
-        };
-        match direction {
-            "forward" => horizontal_position += amount,
-            "up" => depth -= amount,
-            "down" => depth += amount,
-            _ => unreachable!(),
-        };
+use std::io::BufRead;
+
+use crate::util::bigint::BigInt;
+use crate::util::point::{Direction, Point2};
+use crate::util::{read_lines, AdventResult, AdventSolution};
+
+fn parse_command(line: &str) -> Result<(Direction, i64), String> {
+    let (direction, amount) = match line.split_once(' ') {
+        Some(s) => s,
+        None => return Err(format!("Found invalid command: '{}'", line)),
+    };
+    let direction = match direction {
+        // The submarine's x axis runs forward and its y axis runs downward.
+        "forward" => Direction::Right,
+        "up" => Direction::Up,
+        "down" => Direction::Down,
+        d => return Err(format!("Found invalid direction: '{}'", d)),
+    };
+    match amount.parse::<i64>() {
+        Ok(a) => Ok((direction, a)),
+        Err(e) => Err(format!("Failed to parse amount '{}': {}", amount, e)),
     }
-    Ok(AdventSolution::from(horizontal_position * depth))
 }
 
-pub fn part_2(input: &str) -> AdventResult {
-    let mut aim = 0;
-    let mut depth = 0;
-    let mut horizontal_position = 0;
-    for line in input.lines() {
-        let (direction, amount) = {
-            let s: Vec<&str> = line.split(' ').collect();
-            (s[0], s[1].parse::<u64>().unwrap())
-        };
+/// Multiply the coordinates exactly, since on a long enough course the product
+/// doesn't fit in an `i64` even when they do.
+fn position_product(position: Point2) -> AdventSolution {
+    AdventSolution::from(&BigInt::from(position.x) * &BigInt::from(position.y))
+}
+
+/// Move `amount` steps in `direction`, or `None` if the position would no
+/// longer fit in an `i64`.
+fn checked_move(position: Point2, direction: Direction, amount: i64) -> Option<Point2> {
+    let step = direction.unit_vector();
+    Some(Point2::new(
+        position.x.checked_add(step.x.checked_mul(amount)?)?,
+        position.y.checked_add(step.y.checked_mul(amount)?)?,
+    ))
+}
+
+pub fn part_1(input: &mut dyn BufRead) -> AdventResult {
+    let mut position = Point2::ORIGIN;
+    for line in read_lines(input) {
+        let (direction, amount) = parse_command(&line?)?;
+        match checked_move(position, direction, amount) {
+            Some(p) => position = p,
+            None => return Err("Position doesn't fit in an i64".to_string()),
+        }
+    }
+    Ok(position_product(position))
+}
+
+pub fn part_2(input: &mut dyn BufRead) -> AdventResult {
+    let mut aim: i64 = 0;
+    let mut position = Point2::ORIGIN;
+    for line in read_lines(input) {
+        let (direction, amount) = parse_command(&line?)?;
         match direction {
-            "forward" => {
-                horizontal_position += amount;
-                depth += aim * amount;
+            Direction::Right => {
+                let dive = aim
+                    .checked_mul(amount)
+                    .and_then(|d| d.checked_add(position.y));
+                match (position.x.checked_add(amount), dive) {
+                    (Some(x), Some(depth)) => position = Point2::new(x, depth),
+                    _ => return Err("Position doesn't fit in an i64".to_string()),
+                }
             }
-            "up" => aim -= amount,
-            "down" => aim += amount,
-            _ => unreachable!(),
+            d => match checked_move(Point2::new(0, aim), d, amount) {
+                Some(p) => aim = p.y,
+                None => return Err("Aim doesn't fit in an i64".to_string()),
+            },
         };
     }
-    Ok(AdventSolution::from(horizontal_position * depth))
+    Ok(position_product(position))
 }
 
 #[cfg(test)]
@@ -49,14 +83,40 @@ const DAY_02_SAMPLE_INPUT: &str = "forward 5\n\
         forward 2\n";
 
 #[cfg(test)]
-use crate::util::check_solution;
+use crate::util::check_streaming_solution;
 
 #[test]
 fn test_part_1() {
-    check_solution(&DAY_02_SAMPLE_INPUT.to_string(), 150, &part_1);
+    check_streaming_solution(DAY_02_SAMPLE_INPUT, 150, &part_1);
 }
 
 #[test]
 fn test_part_2() {
-    check_solution(&DAY_02_SAMPLE_INPUT.to_string(), 900, &part_2);
+    check_streaming_solution(DAY_02_SAMPLE_INPUT, 900, &part_2);
+}
+
+#[test]
+fn test_large_positions() {
+    // 4 * 10^18 fits in an i64, but its square doesn't.
+    let input = "forward 4000000000000000000\ndown 4000000000000000000\n";
+    let product: BigInt = "16000000000000000000000000000000000000".parse().unwrap();
+    check_streaming_solution(input, product, &part_1);
+    // Positions and aim that don't fit in an i64 are errors, not wrapped
+    // values or panics.
+    let position_error = Err("Position doesn't fit in an i64".to_string());
+    let input = "down 4000000000000000000\nforward 4\n";
+    assert_eq!(part_2(&mut input.as_bytes()), position_error);
+    let input = "down 1\nforward 9223372036854775807\nforward 1\n";
+    assert_eq!(part_1(&mut input.as_bytes()), position_error);
+    assert_eq!(part_2(&mut input.as_bytes()), position_error);
+    let input = "up 9223372036854775807\nup 9223372036854775807\n";
+    assert_eq!(part_1(&mut input.as_bytes()), position_error);
+    assert_eq!(
+        part_2(&mut input.as_bytes()),
+        Err("Aim doesn't fit in an i64".to_string())
+    );
+    let input = "forward -9223372036854775808\n";
+    check_streaming_solution(input, 0, &part_1);
+    let input = "up -9223372036854775808\n";
+    assert_eq!(part_1(&mut input.as_bytes()), position_error);
 }
@@ -1,85 +1,90 @@
+use crate::util::bitset::BitSet;
 use crate::util::{AdventResult, AdventSolution};
 
-pub fn part_1(input: &str) -> AdventResult {
-    let length = input.lines().next().unwrap().len();
-    let mut bit_counts = vec![0; length];
-    for line in input.lines() {
-        for (idx, ch) in line.chars().enumerate() {
-            if ch == '1' {
-                bit_counts[idx] += 1;
-            } else {
-                bit_counts[idx] -= 1;
-            }
+/// The diagnostic report, sliced by bit position: `columns[pos]` holds the
+/// indices of the numbers that have a 1 at `pos`, counting from the most
+/// significant bit.
+struct Report {
+    count: usize,
+    columns: Vec<BitSet>,
+}
+
+fn parse_report(input: &str) -> Result<Report, String> {
+    let lines: Vec<&str> = input.lines().collect();
+    let width = match lines.first() {
+        Some(line) => line.len(),
+        None => return Err(String::from("Failed to find any numbers")),
+    };
+    let mut columns = vec![BitSet::new(lines.len()); width];
+    for (idx, line) in lines.iter().enumerate() {
+        if line.len() != width {
+            return Err(format!("Found number with the wrong length '{}'", line));
         }
-    }
-    let most_common_bits: Vec<bool> = bit_counts.iter().map(|b| b > &0).collect();
-    let (gamma_rate, epsilon_rate) = {
-        let (mut gamma_rate, mut epsilon_rate) = (0, 0);
-        for (idx, bit) in most_common_bits.iter().rev().enumerate() {
-            if *bit {
-                gamma_rate += u64::pow(2, idx as u32);
-            } else {
-                epsilon_rate += u64::pow(2, idx as u32);
+        for (pos, ch) in line.chars().enumerate() {
+            match ch {
+                '1' => columns[pos].set(idx, true),
+                '0' => (),
+                _ => return Err(format!("Found bad binary number '{}'", line)),
             }
         }
-        (gamma_rate, epsilon_rate)
-    };
+    }
+    Ok(Report {
+        count: lines.len(),
+        columns,
+    })
+}
+
+impl Report {
+    /// Read one number back out of the columns.
+    fn value(&self, idx: usize) -> u64 {
+        self.columns
+            .iter()
+            .fold(0, |value, column| value << 1 | column.contains(idx) as u64)
+    }
+}
+
+pub fn part_1(input: &str) -> AdventResult {
+    let report = parse_report(input)?;
+    let (mut gamma_rate, mut epsilon_rate) = (0, 0);
+    for column in report.columns.iter() {
+        let most_common = 2 * column.count() > report.count;
+        gamma_rate = gamma_rate << 1 | most_common as u64;
+        epsilon_rate = epsilon_rate << 1 | !most_common as u64;
+    }
     Ok(AdventSolution::from(gamma_rate * epsilon_rate))
 }
 
+#[derive(Clone, Copy)]
 enum Gas {
     Oxygen,
     CarbonDioxide,
 }
 
-fn filter_numbers(numbers: &[&str], gas: Gas) -> String {
-    let mut remaining_numbers = numbers.to_owned();
-    let length = remaining_numbers[0].len();
-    let mut bit_pos = 0;
-    let mut bit_count;
-    let mut digit: char;
-    while remaining_numbers.len() > 1 && bit_pos < length {
-        bit_count = 0;
-        for number in remaining_numbers.iter() {
-            if number.chars().nth(bit_pos).unwrap() == '1' {
-                bit_count += 1;
-            } else {
-                bit_count -= 1;
-            }
-        }
-        let mut idx = 0;
-        while idx < remaining_numbers.len() {
-            digit = remaining_numbers[idx].chars().nth(bit_pos).unwrap();
-            match gas {
-                Gas::Oxygen => {
-                    if bit_count >= 0 && digit == '0' || bit_count < 0 && digit == '1' {
-                        remaining_numbers.remove(idx);
-                    } else {
-                        idx += 1;
-                    }
-                }
-                Gas::CarbonDioxide => {
-                    if bit_count >= 0 && digit == '1' || bit_count < 0 && digit == '0' {
-                        remaining_numbers.remove(idx);
-                    } else {
-                        idx += 1;
-                    }
-                }
-            }
+fn filter_numbers(report: &Report, gas: Gas) -> Result<u64, String> {
+    let mut remaining = BitSet::full(report.count);
+    for column in report.columns.iter() {
+        if remaining.count() <= 1 {
+            break;
         }
-        bit_pos += 1;
+        let ones = &remaining & column;
+        let zeros = &remaining - column;
+        let ones_most_common = ones.count() >= zeros.count();
+        remaining = match (gas, ones_most_common) {
+            (Gas::Oxygen, true) | (Gas::CarbonDioxide, false) => ones,
+            (Gas::Oxygen, false) | (Gas::CarbonDioxide, true) => zeros,
+        };
+    }
+    match remaining.iter().next() {
+        Some(idx) => Ok(report.value(idx)),
+        None => Err(String::from("Failed to find a rating")),
     }
-    remaining_numbers[0].to_string()
 }
 
 pub fn part_2(input: &str) -> AdventResult {
-    let remaining_numbers: Vec<&str> = input.lines().collect();
-    let oxygen_rating = filter_numbers(&remaining_numbers, Gas::Oxygen);
-    let carbon_dioxide_rating = filter_numbers(&remaining_numbers, Gas::CarbonDioxide);
-    Ok(AdventSolution::from(
-        u64::from_str_radix(&oxygen_rating, 2).unwrap()
-            * u64::from_str_radix(&carbon_dioxide_rating, 2).unwrap(),
-    ))
+    let report = parse_report(input)?;
+    let oxygen_rating = filter_numbers(&report, Gas::Oxygen)?;
+    let carbon_dioxide_rating = filter_numbers(&report, Gas::CarbonDioxide)?;
+    Ok(AdventSolution::from(oxygen_rating * carbon_dioxide_rating))
 }
 
 #[cfg(test)]
@@ -101,10 +106,10 @@ use crate::util::check_solution;
 
 #[test]
 fn test_part_1() {
-    check_solution(&DAY_03_SAMPLE_INPUT.to_string(), 198, &part_1);
+    check_solution(DAY_03_SAMPLE_INPUT, 198, &part_1);
 }
 
 #[test]
 fn test_part_2() {
-    check_solution(&DAY_03_SAMPLE_INPUT.to_string(), 230, &part_2);
+    check_solution(DAY_03_SAMPLE_INPUT, 230, &part_2);
 }
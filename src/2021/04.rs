@@ -1,89 +1,87 @@
+use crate::util::bitset::{BitSet, Bitboard, LineMasks};
+use crate::util::parse::sections;
 use crate::util::{AdventResult, AdventSolution};
 
-type Board = Vec<Vec<(u8, bool)>>;
+const BOARD_SIZE: usize = 5;
 
-fn apply_new_number(number: u8, board: &mut Board) {
-    #![allow(clippy::needless_range_loop)]
-    for row in 0..5 {
-        for col in 0..5 {
-            if board[row][col].0 == number {
-                board[row][col].1 = true;
-            }
-        }
-    }
+struct Board {
+    numbers: Vec<u8>,
+    marked: Bitboard,
 }
 
-fn is_winner(board: &Board) -> bool {
-    #![allow(clippy::ptr_arg)]
-    // check rows
-    for board_row in board.iter().take(5) {
-        if board_row[0].1 && board_row[1].1 && board_row[2].1 && board_row[3].1 && board_row[4].1 {
-            return true;
+impl Board {
+    fn new(numbers: Vec<u8>) -> Self {
+        Board {
+            numbers,
+            marked: Bitboard::new(BOARD_SIZE, BOARD_SIZE),
         }
     }
-    // check columns
-    for col in 0..5 {
-        if board[0][col].1
-            && board[1][col].1
-            && board[2][col].1
-            && board[3][col].1
-            && board[4][col].1
-        {
-            return true;
-        }
-    }
-    false
-}
 
-fn compute_score(board: &Board, last_number: u8) -> u64 {
-    #![allow(clippy::needless_range_loop, clippy::ptr_arg)]
-    let mut sum: u64 = 0;
-    for row in 0..5 {
-        for col in 0..5 {
-            if !board[row][col].1 {
-                sum += board[row][col].0 as u64;
+    fn apply_new_number(&mut self, number: u8) {
+        for (idx, n) in self.numbers.iter().enumerate() {
+            if *n == number {
+                self.marked.cells_mut().set(idx, true);
             }
         }
     }
-    sum * (last_number as u64)
+
+    fn compute_score(&self, last_number: u8) -> u64 {
+        let unmarked_sum: u64 = self
+            .marked
+            .cells()
+            .complement()
+            .iter()
+            .map(|idx| self.numbers[idx] as u64)
+            .sum();
+        unmarked_sum * (last_number as u64)
+    }
+}
+
+fn parse_number(n: &str) -> Result<u8, String> {
+    n.parse::<u8>()
+        .map_err(|e| format!("Failed to parse bingo number '{}': {}", n, e))
 }
 
-fn parse_day_04_input(input: &str) -> (Vec<u8>, Vec<Board>) {
-    let mut lines = input.lines().peekable();
-    let numbers: Vec<u8> = lines
-        .next()
-        .unwrap()
+fn parse_day_04_input(input: &str) -> Result<(Vec<u8>, Vec<Board>), String> {
+    // The first section is the list of numbers, and each one after that is a
+    // board.
+    let sections = sections(input);
+    let (numbers, boards) = match sections.split_first() {
+        Some((first, boards)) if first.len() == 1 => (first[0], boards),
+        _ => return Err(String::from("Failed to find the list of numbers")),
+    };
+    let numbers = numbers
         .split(',')
-        .map(|n| n.parse::<u8>().unwrap())
-        .collect();
-    lines.next();
-    let mut boards: Vec<Board> = Vec::new();
-    let mut new_board: Board;
-    while lines.peek().is_some() {
-        new_board = Vec::new();
-        for _row in 1..=5 {
-            new_board.push(
-                lines
-                    .next()
-                    .unwrap()
-                    .split_ascii_whitespace()
-                    .map(|n| (n.parse::<u8>().unwrap(), false))
-                    .collect(),
-            );
-        }
-        boards.push(new_board);
-        lines.next();
-    }
-    (numbers, boards)
+        .map(parse_number)
+        .collect::<Result<Vec<u8>, String>>()?;
+    let boards = boards
+        .iter()
+        .map(|rows| {
+            let board = rows
+                .iter()
+                .flat_map(|row| row.split_ascii_whitespace())
+                .map(parse_number)
+                .collect::<Result<Vec<u8>, String>>()?;
+            if rows.len() != BOARD_SIZE || board.len() != BOARD_SIZE * BOARD_SIZE {
+                return Err(format!(
+                    "Found a board that isn't {} by {}: {:?}",
+                    BOARD_SIZE, BOARD_SIZE, rows
+                ));
+            }
+            Ok(Board::new(board))
+        })
+        .collect::<Result<Vec<Board>, String>>()?;
+    Ok((numbers, boards))
 }
 
 pub fn part_1(input: &str) -> AdventResult {
-    let (numbers, mut boards) = parse_day_04_input(input);
+    let (numbers, mut boards) = parse_day_04_input(input)?;
+    let masks = LineMasks::new(BOARD_SIZE, BOARD_SIZE);
     for number in numbers.iter() {
         for board in boards.iter_mut() {
-            apply_new_number(*number, board);
-            if is_winner(board) {
-                return Ok(AdventSolution::from(compute_score(board, *number)));
+            board.apply_new_number(*number);
+            if masks.has_complete_line(&board.marked) {
+                return Ok(AdventSolution::from(board.compute_score(*number)));
             }
         }
     }
@@ -91,16 +89,18 @@ pub fn part_1(input: &str) -> AdventResult {
 }
 
 pub fn part_2(input: &str) -> AdventResult {
-    let (numbers, mut boards) = parse_day_04_input(input);
-    let mut winning_boards = vec![false; boards.len()];
+    let (numbers, mut boards) = parse_day_04_input(input)?;
+    let masks = LineMasks::new(BOARD_SIZE, BOARD_SIZE);
+    let mut winning_boards = BitSet::new(boards.len());
     for number in numbers.iter() {
         for (idx, board) in boards.iter_mut().enumerate() {
-            apply_new_number(*number, board);
-            if is_winner(board) && !winning_boards[idx] {
-                winning_boards[idx] = true;
-                if winning_boards.iter().filter(|b| !**b).count() == 0 {
-                    return Ok(AdventSolution::from(compute_score(board, *number)));
-                }
+            board.apply_new_number(*number);
+            // A board only counts as winning the first time it completes a line.
+            if masks.has_complete_line(&board.marked)
+                && winning_boards.insert(idx)
+                && winning_boards.count() == winning_boards.capacity()
+            {
+                return Ok(AdventSolution::from(board.compute_score(*number)));
             }
         }
     }
@@ -134,10 +134,10 @@ use crate::util::check_solution;
 
 #[test]
 fn test_part_1() {
-    check_solution(&DAY_04_SAMPLE_INPUT.to_string(), 4512, &part_1);
+    check_solution(DAY_04_SAMPLE_INPUT, 4512, &part_1);
 }
 
 #[test]
 fn test_part_2() {
-    check_solution(&DAY_04_SAMPLE_INPUT.to_string(), 1924, &part_2);
+    check_solution(DAY_04_SAMPLE_INPUT, 1924, &part_2);
 }
@@ -1,36 +1,88 @@
-use crate::util::{AdventResult, AdventSolution};
+use crate::util::matrix::{BigNatural, Matrix, Modular, Natural, Ring};
+use crate::util::{AdventResult, AdventSolution, Params};
 
-fn simulate_lanternfish(input: &str, days: u16) -> u64 {
-    let initial_lanternfish: Vec<u64> = input
-        .split(',')
-        .map(|f| f.trim_end().parse::<u64>().unwrap())
-        .collect();
-    let mut lanternfish = [0u64; 9];
-    // count the fish in each timer state
-    for fish in initial_lanternfish {
-        lanternfish[fish as usize] += 1;
-    }
-    let mut zero_fish;
-    for _ in 0..days {
-        zero_fish = lanternfish[0];
-        // subtract all fish timers by shifting values left
-        for idx in 0..8 {
-            lanternfish[idx] = lanternfish[idx + 1];
+/// Count the fish in each timer state.
+fn parse_lanternfish(input: &str) -> Result<Vec<u64>, String> {
+    let mut lanternfish = vec![0u64; 9];
+    for fish in input.trim_end().split(',') {
+        match fish.parse::<usize>() {
+            Ok(f) if f < lanternfish.len() => lanternfish[f] += 1,
+            _ => return Err(format!("Found invalid fish timer: '{}'", fish)),
         }
-        // all the fish that were at zero are now at six
-        lanternfish[6] += zero_fish;
-        // and they reproduce, which results in an equal number of fish at eight
-        lanternfish[8] = zero_fish;
     }
-    lanternfish.iter().sum::<u64>()
+    Ok(lanternfish)
+}
+
+/// Build the matrix that advances the fish counts by one day.
+fn lanternfish_step<R: Ring>(ring: &R) -> Matrix<R::Element> {
+    let mut rows = vec![vec![ring.zero(); 9]; 9];
+    // subtract all fish timers by shifting values left
+    for (idx, row) in rows.iter_mut().take(8).enumerate() {
+        row[idx + 1] = ring.one();
+    }
+    // all the fish that were at zero are now at six
+    rows[6][0] = ring.one();
+    // and they reproduce, which results in an equal number of fish at eight
+    rows[8][0] = ring.one();
+    Matrix::from_rows(rows).unwrap()
 }
 
-pub fn part_1(input: &str) -> AdventResult {
-    Ok(AdventSolution::from(simulate_lanternfish(input, 80)))
+/// Count the lanternfish after any number of days, using whatever arithmetic
+/// `ring` provides. Raising the one-day step to a power means this only takes
+/// O(log days) matrix multiplications.
+pub fn simulate_lanternfish<R: Ring>(input: &str, days: u64, ring: &R) -> Result<R::Element, String>
+where
+    R::Element: From<u64>,
+{
+    let lanternfish: Vec<R::Element> = parse_lanternfish(input)?
+        .into_iter()
+        .map(R::Element::from)
+        .collect();
+    let counts = lanternfish_step(ring)
+        .pow(days, ring)
+        .mul_vector(&lanternfish, ring);
+    Ok(counts
+        .iter()
+        .fold(ring.zero(), |total, count| ring.add(&total, count)))
 }
 
-pub fn part_2(input: &str) -> AdventResult {
-    Ok(AdventSolution::from(simulate_lanternfish(input, 256)))
+/// The fish count modulo the `modulus` parameter, if one was given. Working
+/// mod p keeps even a huge number of `days` quick, where the exact count would
+/// have far too many digits.
+fn modular_count(input: &str, days: u64, params: &Params) -> Result<Option<u64>, String> {
+    match params.get_optional("modulus")? {
+        Some(modulus) => match Modular::new(modulus) {
+            Some(ring) => Ok(Some(simulate_lanternfish(input, days, &ring)?)),
+            None => Err("Modulus must be positive".to_string()),
+        },
+        None => Ok(None),
+    }
+}
+
+/// The number of days can be changed with the `days` parameter, and the count
+/// reduced with the `modulus` parameter.
+pub fn part_1_with_params(input: &str, params: &Params) -> AdventResult {
+    let days = params.get("days", 80)?;
+    if let Some(count) = modular_count(input, days, params)? {
+        return Ok(AdventSolution::from(count));
+    }
+    match simulate_lanternfish(input, days, &Natural)? {
+        Some(count) => Ok(AdventSolution::from(count)),
+        None => Err("Fish count doesn't fit in a u64".to_string()),
+    }
+}
+
+/// Like part 1, but the count is exact however large it gets.
+pub fn part_2_with_params(input: &str, params: &Params) -> AdventResult {
+    let days = params.get("days", 256)?;
+    if let Some(count) = modular_count(input, days, params)? {
+        return Ok(AdventSolution::from(count));
+    }
+    Ok(AdventSolution::from(simulate_lanternfish(
+        input,
+        days,
+        &BigNatural,
+    )?))
 }
 
 #[cfg(test)]
@@ -41,10 +93,80 @@ use crate::util::check_solution;
 
 #[test]
 fn test_part_1() {
-    check_solution(&DAY_06_SAMPLE_INPUT, 5934, &part_1);
+    check_solution(DAY_06_SAMPLE_INPUT, 5934, &|input| {
+        part_1_with_params(input, &Params::default())
+    });
 }
 
 #[test]
 fn test_part_2() {
-    check_solution(&DAY_06_SAMPLE_INPUT, 26984457539_u64, &part_2);
+    check_solution(DAY_06_SAMPLE_INPUT, 26984457539_u64, &|input| {
+        part_2_with_params(input, &Params::default())
+    });
+}
+
+#[test]
+fn test_params() {
+    let params = Params::parse(["days=18"].into_iter()).unwrap();
+    check_solution(DAY_06_SAMPLE_INPUT, 26, &|input| {
+        part_1_with_params(input, &params)
+    });
+    check_solution(DAY_06_SAMPLE_INPUT, 26, &|input| {
+        part_2_with_params(input, &params)
+    });
+    // Too many fish for part 1, but part 2 counts them exactly.
+    let params = Params::parse(["days=500"].into_iter()).unwrap();
+    assert!(part_1_with_params(DAY_06_SAMPLE_INPUT, &params).is_err());
+    assert!(part_2_with_params(DAY_06_SAMPLE_INPUT, &params).is_ok());
+    let params = Params::parse(["days=-1"].into_iter()).unwrap();
+    assert!(part_1_with_params(DAY_06_SAMPLE_INPUT, &params).is_err());
+    // A modulus makes even 10^15 days quick.
+    let params = Params::parse(["days=256", "modulus=1000000007"].into_iter()).unwrap();
+    check_solution(
+        DAY_06_SAMPLE_INPUT,
+        26984457539_u64 % 1_000_000_007,
+        &|input| part_2_with_params(input, &params),
+    );
+    let params = Params::parse(["days=1000000000000000", "modulus=1000"].into_iter()).unwrap();
+    let count = part_2_with_params(DAY_06_SAMPLE_INPUT, &params).unwrap();
+    assert_eq!(part_1_with_params(DAY_06_SAMPLE_INPUT, &params), Ok(count));
+    let params = Params::parse(["modulus=0"].into_iter()).unwrap();
+    assert_eq!(
+        part_2_with_params(DAY_06_SAMPLE_INPUT, &params),
+        Err("Modulus must be positive".to_string())
+    );
+}
+
+#[test]
+fn test_simulate_lanternfish() {
+    use crate::util::bigint::BigUint;
+
+    assert_eq!(
+        simulate_lanternfish(DAY_06_SAMPLE_INPUT, 18, &Natural),
+        Ok(Some(26))
+    );
+    assert_eq!(
+        simulate_lanternfish(DAY_06_SAMPLE_INPUT, 0, &Natural),
+        Ok(Some(5))
+    );
+    assert_eq!(
+        simulate_lanternfish(DAY_06_SAMPLE_INPUT, 500, &Natural),
+        Ok(None)
+    );
+    let p = 1_000_000_007;
+    let ring = Modular::new(p).unwrap();
+    assert_eq!(
+        simulate_lanternfish(DAY_06_SAMPLE_INPUT, 256, &ring),
+        Ok(26984457539 % p)
+    );
+    // Far more days than could ever be simulated one at a time
+    let a = simulate_lanternfish(DAY_06_SAMPLE_INPUT, 1_000_000_000_000_000, &ring);
+    assert!(a.is_ok_and(|a| a < p));
+    // Exact counts keep going past the point where they overflow a u64
+    let exact = simulate_lanternfish(DAY_06_SAMPLE_INPUT, 500, &BigNatural).unwrap();
+    assert!(exact.bits() > 64);
+    assert_eq!(
+        exact % BigUint::from(p),
+        BigUint::from(simulate_lanternfish(DAY_06_SAMPLE_INPUT, 500, &ring).unwrap())
+    );
 }
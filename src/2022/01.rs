@@ -1,43 +1,47 @@
+use std::io::BufRead;
+
+use crate::util::parse::read_sections;
 use crate::util::{AdventResult, AdventSolution};
 
-fn parse_elf_calories(input: &str) -> Result<Vec<u64>, String> {
-    let mut elf_calories = Vec::new();
-    let mut current_total = 0;
-    for line in input.lines() {
-        match line {
-            // A blank line indicates the end of an elf's inventory.
-            "" => {
-                elf_calories.push(current_total);
-                current_total = 0;
-            }
-            _ => {
-                current_total += match line.parse::<u64>() {
-                    Ok(l) => l,
-                    Err(e) => return Err(format!("Failed to parse calories '{}': {}", line, e)),
-                }
-            }
+/// Call `f` with each elf's total calories, without keeping the whole list.
+fn for_each_elf(input: &mut dyn BufRead, mut f: impl FnMut(u64)) -> Result<(), String> {
+    // Each elf's inventory is a section of lines.
+    for inventory in read_sections(input) {
+        let mut total = 0;
+        for line in inventory? {
+            total += match line.parse::<u64>() {
+                Ok(l) => l,
+                Err(e) => return Err(format!("Failed to parse calories '{}': {}", line, e)),
+            };
         }
+        f(total);
     }
-    Ok(elf_calories)
+    Ok(())
 }
 
-pub fn part_1(input: &str) -> AdventResult {
-    let elf_calories = parse_elf_calories(input)?;
-    match elf_calories.iter().max() {
-        Some(m) => Ok(AdventSolution::from(*m)),
+pub fn part_1(input: &mut dyn BufRead) -> AdventResult {
+    let mut most = None;
+    for_each_elf(input, |total| most = most.max(Some(total)))?;
+    match most {
+        Some(m) => Ok(AdventSolution::from(m)),
         None => Err("No calories found".to_string()),
     }
 }
 
-pub fn part_2(input: &str) -> AdventResult {
-    let mut elf_calories = parse_elf_calories(input)?;
-    elf_calories.sort();
-    if elf_calories.len() >= 3 {
-        Ok(AdventSolution::from(
-            elf_calories.iter().rev().take(3).sum::<u64>(),
-        ))
-    } else {
-        Err("Not enough elves".to_string())
+pub fn part_2(input: &mut dyn BufRead) -> AdventResult {
+    // The three largest totals so far, largest first
+    let mut top = [None; 3];
+    for_each_elf(input, |total| {
+        let mut total = Some(total);
+        for slot in top.iter_mut() {
+            if total > *slot {
+                std::mem::swap(slot, &mut total);
+            }
+        }
+    })?;
+    match top {
+        [Some(a), Some(b), Some(c)] => Ok(AdventSolution::from(a + b + c)),
+        _ => Err("Not enough elves".to_string()),
     }
 }
 
@@ -58,14 +62,21 @@ const DAY_01_SAMPLE_INPUT: &str = "1000\n\
     10000\n\n";
 
 #[cfg(test)]
-use crate::util::check_solution;
+use crate::util::check_streaming_solution;
 
 #[test]
 fn test_part_1() {
-    check_solution(&DAY_01_SAMPLE_INPUT.to_string(), 24000, &part_1);
+    check_streaming_solution(DAY_01_SAMPLE_INPUT, 24000, &part_1);
 }
 
 #[test]
 fn test_part_2() {
-    check_solution(&DAY_01_SAMPLE_INPUT.to_string(), 45000, &part_2);
+    check_streaming_solution(DAY_01_SAMPLE_INPUT, 45000, &part_2);
+}
+
+#[test]
+fn test_last_elf_without_blank_line() {
+    // The last elf used to be dropped when there was no blank line after it.
+    check_streaming_solution("1000\n\n2000\n3000", 5000, &part_1);
+    check_streaming_solution("1000\r\n\r\n2000\r\n3000\r\n", 5000, &part_1);
 }
@@ -96,10 +96,10 @@ use crate::util::check_solution;
 
 #[test]
 fn test_part_1() {
-    check_solution(&DAY_02_SAMPLE_INPUT.to_string(), 15, &part_1);
+    check_solution(DAY_02_SAMPLE_INPUT, 15, &part_1);
 }
 
 #[test]
 fn test_part_2() {
-    check_solution(&DAY_02_SAMPLE_INPUT.to_string(), 12, &part_2);
+    check_solution(DAY_02_SAMPLE_INPUT, 12, &part_2);
 }
@@ -52,7 +52,7 @@ pub fn part_1(input: &str) -> AdventResult {
 pub fn part_2(input: &str) -> AdventResult {
     let mut total_priority = 0;
     // Subtract one here to account for the trailing new line.
-    if (input.lines().count() - 1) % 3 != 0 {
+    if !(input.lines().count() - 1).is_multiple_of(3) {
         return Err("Elves are not in groups of three".to_string());
     }
     let mut rucksack_iter = input.lines();
@@ -97,10 +97,10 @@ use crate::util::check_solution;
 
 #[test]
 fn test_part_1() {
-    check_solution(&DAY_03_SAMPLE_INPUT, 157, &part_1);
+    check_solution(DAY_03_SAMPLE_INPUT, 157, &part_1);
 }
 
 #[test]
 fn test_part_2() {
-    check_solution(&DAY_03_SAMPLE_INPUT, 70, &part_2);
+    check_solution(DAY_03_SAMPLE_INPUT, 70, &part_2);
 }
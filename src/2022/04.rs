@@ -1,23 +1,19 @@
 use regex::Regex;
 
+use crate::util::interval::Interval;
 use crate::util::{AdventResult, AdventSolution};
 
 struct ElfPair {
-    first_start: u64,
-    first_end: u64,
-    second_start: u64,
-    second_end: u64,
+    first: Interval<u64>,
+    second: Interval<u64>,
 }
 
 impl ElfPair {
     fn is_complete_overlap(&self) -> bool {
-        (self.first_start >= self.second_start && self.first_end <= self.second_end)
-            || (self.second_start >= self.first_start && self.second_end <= self.first_end)
+        self.first.contains_interval(&self.second) || self.second.contains_interval(&self.first)
     }
     fn is_partial_overlap(&self) -> bool {
-        (self.second_start <= self.first_start && self.first_start <= self.second_end)
-            || (self.second_start <= self.first_end && self.first_end <= self.second_end)
-            || self.is_complete_overlap()
+        self.first.overlaps(&self.second)
     }
 }
 
@@ -36,12 +32,16 @@ fn parse_elf_pairs(pairs: &str) -> Result<Vec<ElfPair>, String> {
                     caps[3].parse::<u64>().unwrap(),
                     caps[4].parse::<u64>().unwrap(),
                 );
-                elf_pairs.push(ElfPair {
-                    first_start,
-                    first_end,
-                    second_start,
-                    second_end,
-                });
+                if first_start > first_end || second_start > second_end {
+                    return Err(format!("Found reversed range in pair: '{}'", pair));
+                }
+                match (
+                    Interval::inclusive(first_start, first_end),
+                    Interval::inclusive(second_start, second_end),
+                ) {
+                    (Some(first), Some(second)) => elf_pairs.push(ElfPair { first, second }),
+                    _ => return Err(format!("Found section out of range in pair: '{}'", pair)),
+                }
             }
             None => return Err(format!("Found invalid pair: '{}'", pair)),
         }
@@ -76,10 +76,22 @@ use crate::util::check_solution;
 
 #[test]
 fn test_part_1() {
-    check_solution(&DAY_04_SAMPLE_INPUT, 2, &part_1);
+    check_solution(DAY_04_SAMPLE_INPUT, 2, &part_1);
 }
 
 #[test]
 fn test_part_2() {
-    check_solution(&DAY_04_SAMPLE_INPUT, 4, &part_2);
+    check_solution(DAY_04_SAMPLE_INPUT, 4, &part_2);
+}
+
+#[test]
+fn test_invalid_pairs() {
+    assert_eq!(
+        part_1("5-3,1-2\n").err(),
+        Some("Found reversed range in pair: '5-3,1-2'".to_string())
+    );
+    assert_eq!(
+        part_2("1-18446744073709551615,2-3\n").err(),
+        Some("Found section out of range in pair: '1-18446744073709551615,2-3'".to_string())
+    );
 }
@@ -1,5 +1,6 @@
 use regex::Regex;
 
+use crate::util::parse::{parse_column_diagram, sections};
 use crate::util::{AdventResult, AdventSolution};
 
 struct CraneMove {
@@ -8,65 +9,55 @@ struct CraneMove {
     quantity: usize,
 }
 
-fn parse_stacks(input: &str) -> Result<Vec<Vec<char>>, String> {
-    // Get number of stacks by looking at the label row.
-    let stack_label_re = Regex::new(r"^[\s\d]+$").unwrap();
-    let mut num_stacks = 0;
-    for line in input.lines() {
-        if stack_label_re.is_match(line) {
-            num_stacks = line
-                .chars()
-                .nth_back(1)
-                .unwrap()
-                .to_string()
-                .parse()
-                .unwrap();
+/// Split the input into the starting stacks and the list of moves, which are
+/// separated by a blank line.
+fn parse_day_05_input(input: &str) -> Result<(Vec<Vec<char>>, Vec<CraneMove>), String> {
+    let sections = sections(input);
+    let (diagram, moves) = match &sections[..] {
+        [diagram, moves] => (parse_column_diagram(diagram)?, moves),
+        [diagram] => (parse_column_diagram(diagram)?, &Vec::new()),
+        _ => return Err("Failed to find the stacks and the moves".to_string()),
+    };
+    for (label, stack) in diagram.labels.iter().zip(&diagram.stacks) {
+        if let Some(c) = stack.iter().find(|c| !c.is_ascii_uppercase()) {
+            return Err(format!(
+                "Found invalid crate letter '{}' in stack {}",
+                c, label
+            ));
         }
     }
-    if num_stacks == 0 {
-        return Err("Failed to find stack label row".to_string());
-    }
-
-    // Assemble the stacks into character vectors.
-    let mut stacks = vec![vec![]; num_stacks];
-    for line in input.lines() {
-        if stack_label_re.is_match(line) {
-            break;
-        }
-        for (idx, crate_letter) in line.chars().skip(1).step_by(4).enumerate() {
-            match crate_letter {
-                ' ' => (),
-                c if c.is_ascii_uppercase() => stacks[idx].insert(0, c),
-                _ => return Err(format!("Found invalid crate letter '{}'", crate_letter)),
-            }
-        }
-    }
-
-    Ok(stacks)
+    let crane_moves = parse_crane_moves(moves, |label| diagram.index_of(label))?;
+    Ok((diagram.stacks, crane_moves))
 }
 
-fn parse_crane_moves(input: &str) -> Vec<CraneMove> {
+fn parse_crane_moves(
+    lines: &[&str],
+    stack_index: impl Fn(&str) -> Option<usize>,
+) -> Result<Vec<CraneMove>, String> {
+    let move_re = Regex::new(r"^move (\d+) from (\w+) to (\w+)$").unwrap();
     let mut crane_moves = Vec::new();
-    let move_re = Regex::new(r"^move (\d+) from (\d+) to (\d+)").unwrap();
-    for line in input.lines() {
-        match move_re.captures(line) {
-            Some(caps) => {
-                let (quantity, from, to) = (
-                    caps[1].parse::<usize>().unwrap(),
-                    caps[2].parse::<usize>().unwrap() - 1,
-                    caps[3].parse::<usize>().unwrap() - 1,
-                );
-                crane_moves.push(CraneMove { from, to, quantity });
-            }
-            None => continue,
-        }
+    for line in lines {
+        let caps = match move_re.captures(line) {
+            Some(c) => c,
+            None => return Err(format!("Failed to parse crane move '{}'", line)),
+        };
+        let stack = |label: &str| match stack_index(label) {
+            Some(idx) => Ok(idx),
+            None => Err(format!("Found move with unknown stack '{}'", label)),
+        };
+        crane_moves.push(CraneMove {
+            quantity: caps[1]
+                .parse::<usize>()
+                .map_err(|e| format!("Failed to parse crane move '{}': {}", line, e))?,
+            from: stack(&caps[2])?,
+            to: stack(&caps[3])?,
+        });
     }
-    crane_moves
+    Ok(crane_moves)
 }
 
 pub fn part_1(input: &str) -> AdventResult {
-    let mut stacks = parse_stacks(input)?;
-    let crane_moves = parse_crane_moves(input);
+    let (mut stacks, crane_moves) = parse_day_05_input(input)?;
 
     for crane_move in crane_moves {
         let CraneMove { from, to, quantity } = crane_move;
@@ -84,8 +75,7 @@ pub fn part_1(input: &str) -> AdventResult {
 }
 
 pub fn part_2(input: &str) -> AdventResult {
-    let mut stacks = parse_stacks(input)?;
-    let crane_moves = parse_crane_moves(input);
+    let (mut stacks, crane_moves) = parse_day_05_input(input)?;
 
     for crane_move in crane_moves {
         let CraneMove { from, to, quantity } = crane_move;
@@ -1,5 +1,6 @@
-use clap::{App, Arg};
-use std::fs;
+use clap::{App, AppSettings, Arg, SubCommand};
+use std::fs::{self, File};
+use std::io::{self, BufReader};
 
 #[path = "2015/mod.rs"]
 mod solve_2015;
@@ -17,18 +18,45 @@ mod solve_2021;
 mod solve_2022;
 
 mod util;
-use util::not_solved_yet;
+use util::json::parse_json;
+use util::{not_solved_yet, AdventResult, AdventSolution, Params, StreamingSolver};
+
+use solve_2019::intcode::ascii::{play, Ascii};
+use solve_2019::intcode::assemble::{assemble, format_program};
+use solve_2019::intcode::debug::{run_debugger, Debugger};
+use solve_2019::intcode::disassemble::disassemble;
+use solve_2019::intcode::profile::Profiler;
+use solve_2019::intcode::{parse_program, Intcode};
 
 fn main() -> Result<(), String> {
     // parse command line arguments
-    // advent-of-code <YEAR> <DAY> <PART> <INPUT>
+    // advent-of-code [--json] [--verify <FILE>] [--param <KEY=VALUE>...] <YEAR> <DAY> <PART> <INPUT>
+    // advent-of-code assemble <INPUT>
+    // advent-of-code debug [--script <FILE>] <INPUT>
+    // advent-of-code disassemble <INPUT>
+    // advent-of-code play [--record <FILE>] [--replay <FILE>] <INPUT>
+    // advent-of-code profile [--inputs <VALUES>] [--listing] <INPUT>
     const YEAR: &str = "YEAR";
     const DAY: &str = "DAY";
     const INPUT: &str = "INPUT";
     const PART: &str = "PART";
+    const JSON: &str = "json";
+    const VERIFY: &str = "verify";
+    const PARAM: &str = "param";
+    const ASSEMBLE: &str = "assemble";
+    const DEBUG: &str = "debug";
+    const SCRIPT: &str = "script";
+    const DISASSEMBLE: &str = "disassemble";
+    const PLAY: &str = "play";
+    const RECORD: &str = "record";
+    const REPLAY: &str = "replay";
+    const PROFILE: &str = "profile";
+    const INPUTS: &str = "inputs";
+    const LISTING: &str = "listing";
     let matches = App::new("Advent of Code")
         .version("0.1.0")
         .author("Bradley Gannon <bradley@bradleygannon.com>")
+        .setting(AppSettings::SubcommandsNegateReqs)
         .arg(
             Arg::with_name(YEAR)
                 .help("Must be between 2015 and 2022, inclusive")
@@ -45,8 +73,168 @@ fn main() -> Result<(), String> {
                 .help("Path to file containing puzzle input")
                 .required(true),
         )
+        .arg(
+            Arg::with_name(JSON)
+                .long(JSON)
+                .help("Print the solution as JSON, including its type"),
+        )
+        .arg(
+            Arg::with_name(VERIFY)
+                .long(VERIFY)
+                .value_name("FILE")
+                .help("Check the solution against the JSON answer in FILE"),
+        )
+        .arg(
+            Arg::with_name(PARAM)
+                .long(PARAM)
+                .value_name("KEY=VALUE")
+                .multiple(true)
+                .number_of_values(1)
+                .help("Pass a setting to solvers that accept them (e.g. 2020 day 1)"),
+        )
+        .subcommand(
+            SubCommand::with_name(ASSEMBLE)
+                .about("Turn Intcode assembly into a comma-separated program")
+                .arg(
+                    Arg::with_name(INPUT)
+                        .help("Path to file containing Intcode assembly")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(DEBUG)
+                .about("Step through an Intcode program")
+                .arg(
+                    Arg::with_name(INPUT)
+                        .help("Path to file containing an Intcode program")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name(SCRIPT)
+                        .long(SCRIPT)
+                        .value_name("FILE")
+                        .help("Read debugger commands from FILE instead of the terminal"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(DISASSEMBLE)
+                .about("Print an annotated listing of an Intcode program")
+                .arg(
+                    Arg::with_name(INPUT)
+                        .help("Path to file containing an Intcode program")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(PLAY)
+                .about("Play a text-based Intcode program in the terminal")
+                .arg(
+                    Arg::with_name(INPUT)
+                        .help("Path to file containing an Intcode program")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name(RECORD)
+                        .long(RECORD)
+                        .value_name("FILE")
+                        .help("Save each line of input to FILE"),
+                )
+                .arg(
+                    Arg::with_name(REPLAY)
+                        .long(REPLAY)
+                        .value_name("FILE")
+                        .help("Send the lines in FILE before reading from the terminal"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(PROFILE)
+                .about("Run an Intcode program and report where it spent its time")
+                .arg(
+                    Arg::with_name(INPUT)
+                        .help("Path to file containing an Intcode program")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name(INPUTS)
+                        .long(INPUTS)
+                        .value_name("VALUES")
+                        .help("Comma-separated input values for the program"),
+                )
+                .arg(
+                    Arg::with_name(LISTING)
+                        .long(LISTING)
+                        .help("Also print a disassembly with the count for each instruction"),
+                ),
+        )
         .get_matches();
 
+    // the Intcode tools don't solve anything, so they skip the usual arguments
+    if let Some(matches) = matches.subcommand_matches(ASSEMBLE) {
+        let path = matches.value_of(INPUT).unwrap();
+        let source = match fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) => return Err(format!("Failed to read assembly file: {}", e)),
+        };
+        println!("{}", format_program(&assemble(&source)?));
+        return Ok(());
+    }
+    if let Some(matches) = matches.subcommand_matches(DEBUG) {
+        let program = read_intcode_program(matches.value_of(INPUT).unwrap())?;
+        let mut debugger = Debugger::new(Intcode::new(program));
+        let mut stdout = io::stdout();
+        return match matches.value_of(SCRIPT) {
+            Some(path) => match File::open(path) {
+                Ok(f) => run_debugger(&mut debugger, &mut BufReader::new(f), &mut stdout, false),
+                Err(e) => Err(format!("Failed to read script file: {}", e)),
+            },
+            None => run_debugger(&mut debugger, &mut io::stdin().lock(), &mut stdout, true),
+        };
+    }
+    if let Some(matches) = matches.subcommand_matches(PLAY) {
+        let program = read_intcode_program(matches.value_of(INPUT).unwrap())?;
+        let replay: Vec<String> = match matches.value_of(REPLAY).map(fs::read_to_string) {
+            Some(Ok(transcript)) => transcript.lines().map(|l| l.to_string()).collect(),
+            Some(Err(e)) => return Err(format!("Failed to read transcript: {}", e)),
+            None => Vec::new(),
+        };
+        let mut record = match matches.value_of(RECORD).map(File::create) {
+            Some(Ok(f)) => Some(f),
+            Some(Err(e)) => return Err(format!("Failed to create transcript: {}", e)),
+            None => None,
+        };
+        return play(
+            &mut Ascii::new(Intcode::new(program)),
+            &replay,
+            &mut io::stdin().lock(),
+            &mut io::stdout(),
+            record.as_mut().map(|f| f as &mut dyn io::Write),
+        );
+    }
+    if let Some(matches) = matches.subcommand_matches(PROFILE) {
+        let program = read_intcode_program(matches.value_of(INPUT).unwrap())?;
+        let inputs = match matches.value_of(INPUTS) {
+            Some(values) => parse_program(values)?,
+            None => Vec::new(),
+        };
+        let mut profiler = Profiler::new(Intcode::new(program.clone()));
+        // report on however far the program got, even if it failed
+        let result = profiler.run_with_inputs(&inputs);
+        if let Ok(outputs) = &result {
+            println!("Output: {}", format_program(outputs));
+        }
+        print!("\n{}", profiler.profile().report());
+        if matches.is_present(LISTING) {
+            print!("\n{}", profiler.profile().heat_listing(&program));
+        }
+        result?;
+        return Ok(());
+    }
+    if let Some(matches) = matches.subcommand_matches(DISASSEMBLE) {
+        let program = read_intcode_program(matches.value_of(INPUT).unwrap())?;
+        print!("{}", disassemble(&program));
+        return Ok(());
+    }
+
     // convert YEAR, DAY, and PART to numbers and check bounds
     let year = match matches.value_of(YEAR).unwrap().to_string().parse::<u16>() {
         Ok(y) if (2015..=2022).contains(&y) => y,
@@ -74,87 +262,152 @@ fn main() -> Result<(), String> {
         Err(e) => return Err(format!("Failed to parse PART: {}", e)),
     };
 
-    // read the input file
-    let input = match fs::read_to_string(matches.value_of(INPUT).unwrap()) {
-        Ok(i) => i,
-        Err(e) => return Err(format!("Failed to read input file: {}", e)),
+    let params = Params::parse(matches.values_of(PARAM).into_iter().flatten())?;
+    params.check_keys(solver_params(year, day))?;
+
+    // stream the input file to the solver if it can take it that way, and
+    // otherwise read the whole thing first
+    let path = matches.value_of(INPUT).unwrap();
+    let solution = match streaming_solver(year, day, part) {
+        Some(solver) => match File::open(path) {
+            Ok(f) => solver(&mut BufReader::new(f)),
+            Err(e) => return Err(format!("Failed to read input file: {}", e)),
+        },
+        None => match fs::read_to_string(path) {
+            Ok(input) => solve(year, day, part, &input, &params),
+            Err(e) => return Err(format!("Failed to read input file: {}", e)),
+        },
     };
 
-    // pass the puzzle input to the solution function
-    let solution = match year {
+    // print the solution
+    let solution = solution?;
+    if matches.is_present(JSON) {
+        println!("{}", solution.to_json());
+    } else {
+        println!("{}", solution);
+    }
+
+    // compare against the expected answer, if there is one
+    if let Some(path) = matches.value_of(VERIFY) {
+        let expected = match fs::read_to_string(path) {
+            Ok(e) => AdventSolution::from_json(&parse_json(&e)?)?,
+            Err(e) => return Err(format!("Failed to read answer file: {}", e)),
+        };
+        if solution != expected {
+            return Err(format!(
+                "Solution doesn't match the answer in '{}' (expected {})",
+                path,
+                expected.to_json()
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn read_intcode_program(path: &str) -> Result<Vec<i64>, String> {
+    match fs::read_to_string(path) {
+        Ok(input) => Ok(parse_program(&input)?),
+        Err(e) => Err(format!("Failed to read program file: {}", e)),
+    }
+}
+
+/// The parameters that each day's solvers accept through `--param`.
+fn solver_params(year: u16, day: u8) -> &'static [&'static str] {
+    match (year, day) {
+        (2020, 1) => &["k", "target"],
+        (2021, 6) => &["days", "modulus"],
+        _ => &[],
+    }
+}
+
+/// Days whose solvers read the input a line at a time. These are checked
+/// before the whole input is read into memory.
+fn streaming_solver(year: u16, day: u8, part: u8) -> Option<StreamingSolver> {
+    let solver: StreamingSolver = match (year, day, part) {
+        (2020, 2, 1) => solve_2020::day_02::part_1,
+        (2020, 2, 2) => solve_2020::day_02::part_2,
+        (2021, 1, 1) => solve_2021::day_01::part_1,
+        (2021, 1, 2) => solve_2021::day_01::part_2,
+        (2021, 2, 1) => solve_2021::day_02::part_1,
+        (2021, 2, 2) => solve_2021::day_02::part_2,
+        (2022, 1, 1) => solve_2022::day_01::part_1,
+        (2022, 1, 2) => solve_2022::day_01::part_2,
+        _ => return None,
+    };
+    Some(solver)
+}
+
+/// Pass the whole puzzle input to the solution function.
+fn solve(year: u16, day: u8, part: u8, input: &str, params: &Params) -> AdventResult {
+    match year {
         2015 => match (day, part) {
-            (1, 1) => solve_2015::day_01::part_1(&input),
-            (1, 2) => solve_2015::day_01::part_2(&input),
-            (2, 1) => solve_2015::day_02::part_1(&input),
-            (2, 2) => solve_2015::day_02::part_2(&input),
-            (3, 1) => solve_2015::day_03::part_1(&input),
-            (3, 2) => solve_2015::day_03::part_2(&input),
-            (4, 1) => solve_2015::day_04::part_1(&input),
-            (4, 2) => solve_2015::day_04::part_2(&input),
-            (5, 1) => solve_2015::day_05::part_1(&input),
-            (5, 2) => solve_2015::day_05::part_2(&input),
-            (6, 1) => solve_2015::day_06::part_1(&input),
-            (6, 2) => solve_2015::day_06::part_2(&input),
-            (10, 1) => solve_2015::day_10::part_1(&input),
-            (12, 1) => solve_2015::day_12::part_1(&input),
-            (25, 1) => solve_2015::day_25::part_1(&input),
+            (1, 1) => solve_2015::day_01::part_1(input),
+            (1, 2) => solve_2015::day_01::part_2(input),
+            (2, 1) => solve_2015::day_02::part_1(input),
+            (2, 2) => solve_2015::day_02::part_2(input),
+            (3, 1) => solve_2015::day_03::part_1(input),
+            (3, 2) => solve_2015::day_03::part_2(input),
+            (4, 1) => solve_2015::day_04::part_1(input),
+            (4, 2) => solve_2015::day_04::part_2(input),
+            (5, 1) => solve_2015::day_05::part_1(input),
+            (5, 2) => solve_2015::day_05::part_2(input),
+            (6, 1) => solve_2015::day_06::part_1(input),
+            (6, 2) => solve_2015::day_06::part_2(input),
+            (10, 1) => solve_2015::day_10::part_1(input),
+            (12, 1) => solve_2015::day_12::part_1(input),
+            (25, 1) => solve_2015::day_25::part_1(input),
             (d, p) => not_solved_yet(2015, d, p),
         },
         2016 => not_solved_yet(2016, day, part),
         2017 => not_solved_yet(2017, day, part),
         2018 => not_solved_yet(2018, day, part),
-        2019 => not_solved_yet(2019, day, part),
+        2019 => match (day, part) {
+            (2, 1) => solve_2019::day_02::part_1(input),
+            (2, 2) => solve_2019::day_02::part_2(input),
+            (5, 1) => solve_2019::day_05::part_1(input),
+            (5, 2) => solve_2019::day_05::part_2(input),
+            (7, 1) => solve_2019::day_07::part_1(input),
+            (7, 2) => solve_2019::day_07::part_2(input),
+            (8, 1) => solve_2019::day_08::part_1(input),
+            (8, 2) => solve_2019::day_08::part_2(input),
+            (9, 1) => solve_2019::day_09::part_1(input),
+            (9, 2) => solve_2019::day_09::part_2(input),
+            (d, p) => not_solved_yet(2019, d, p),
+        },
         2020 => match (day, part) {
-            (1, 1) => solve_2020::day_01::part_1(&input),
-            (1, 2) => solve_2020::day_01::part_2(&input),
-            (2, 1) => solve_2020::day_02::part_1(&input),
-            (2, 2) => solve_2020::day_02::part_2(&input),
-            (3, 1) => solve_2020::day_03::part_1(&input),
-            (3, 2) => solve_2020::day_03::part_2(&input),
-            (25, 1) => solve_2020::day_25::part_1(&input),
+            (1, 1) => solve_2020::day_01::part_1_with_params(input, params),
+            (1, 2) => solve_2020::day_01::part_2_with_params(input, params),
+            (3, 1) => solve_2020::day_03::part_1(input),
+            (3, 2) => solve_2020::day_03::part_2(input),
+            (25, 1) => solve_2020::day_25::part_1(input),
             (d, p) => not_solved_yet(2020, d, p),
         },
         2021 => match (day, part) {
-            (1, 1) => solve_2021::day_01::part_1(&input),
-            (1, 2) => solve_2021::day_01::part_2(&input),
-            (2, 1) => solve_2021::day_02::part_1(&input),
-            (2, 2) => solve_2021::day_02::part_2(&input),
-            (3, 1) => solve_2021::day_03::part_1(&input),
-            (3, 2) => solve_2021::day_03::part_2(&input),
-            (4, 1) => solve_2021::day_04::part_1(&input),
-            (4, 2) => solve_2021::day_04::part_2(&input),
-            (5, 1) => solve_2021::day_05::part_1(&input),
-            (5, 2) => solve_2021::day_05::part_2(&input),
-            (6, 1) => solve_2021::day_06::part_1(&input),
-            (6, 2) => solve_2021::day_06::part_2(&input),
+            (3, 1) => solve_2021::day_03::part_1(input),
+            (3, 2) => solve_2021::day_03::part_2(input),
+            (4, 1) => solve_2021::day_04::part_1(input),
+            (4, 2) => solve_2021::day_04::part_2(input),
+            (5, 1) => solve_2021::day_05::part_1(input),
+            (5, 2) => solve_2021::day_05::part_2(input),
+            (6, 1) => solve_2021::day_06::part_1_with_params(input, params),
+            (6, 2) => solve_2021::day_06::part_2_with_params(input, params),
             (d, p) => not_solved_yet(2021, d, p),
         },
         2022 => match (day, part) {
-            (1, 1) => solve_2022::day_01::part_1(&input),
-            (1, 2) => solve_2022::day_01::part_2(&input),
-            (2, 1) => solve_2022::day_02::part_1(&input),
-            (2, 2) => solve_2022::day_02::part_2(&input),
-            (3, 1) => solve_2022::day_03::part_1(&input),
-            (3, 2) => solve_2022::day_03::part_2(&input),
-            (4, 1) => solve_2022::day_04::part_1(&input),
-            (4, 2) => solve_2022::day_04::part_2(&input),
-            (5, 1) => solve_2022::day_05::part_1(&input),
-            (5, 2) => solve_2022::day_05::part_2(&input),
-            (6, 1) => solve_2022::day_06::part_1(&input),
-            (6, 2) => solve_2022::day_06::part_2(&input),
-            (7, 1) => solve_2022::day_07::part_1(&input),
-            (7, 2) => solve_2022::day_07::part_2(&input),
+            (2, 1) => solve_2022::day_02::part_1(input),
+            (2, 2) => solve_2022::day_02::part_2(input),
+            (3, 1) => solve_2022::day_03::part_1(input),
+            (3, 2) => solve_2022::day_03::part_2(input),
+            (4, 1) => solve_2022::day_04::part_1(input),
+            (4, 2) => solve_2022::day_04::part_2(input),
+            (5, 1) => solve_2022::day_05::part_1(input),
+            (5, 2) => solve_2022::day_05::part_2(input),
+            (6, 1) => solve_2022::day_06::part_1(input),
+            (6, 2) => solve_2022::day_06::part_2(input),
+            (7, 1) => solve_2022::day_07::part_1(input),
+            (7, 2) => solve_2022::day_07::part_2(input),
             (d, p) => not_solved_yet(2022, d, p),
         },
         _ => unreachable!(),
-    };
-
-    // print the solution
-    match solution {
-        Ok(s) => {
-            println!("{}", s);
-            Ok(())
-        }
-        Err(e) => Err(e),
     }
 }
@@ -1,38 +1,161 @@
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Display};
+use std::io::BufRead;
+use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct AdventSolution(String);
+use bigint::{BigInt, BigUint};
+use json::Json;
+
+pub mod bigint;
+pub mod bitset;
+pub mod combinatorics;
+pub mod cycle;
+pub mod graph;
+pub mod interval;
+pub mod json;
+pub mod matrix;
+pub mod number_theory;
+pub mod ocr;
+pub mod parse;
+pub mod point;
+pub mod segment;
+
+/// The answer to one part of a puzzle.
+#[derive(Clone, Debug)]
+pub enum AdventSolution {
+    Integer(BigInt),
+    Text(String),
+    /// A multi-line grid, usually block letters drawn with `#` and `.`, that
+    /// has to be read by eye (or by OCR).
+    Display(String),
+}
 
 macro_rules! from_for_advent_solution {
-    ($from_type: ty) => {
+    ($from_type: ty, $via: ty) => {
         impl From<$from_type> for AdventSolution {
             fn from(x: $from_type) -> Self {
-                AdventSolution(x.to_string())
+                AdventSolution::Integer(BigInt::from(x as $via))
             }
         }
     };
 }
 
-from_for_advent_solution!(i32);
-from_for_advent_solution!(i64);
-from_for_advent_solution!(u64);
-from_for_advent_solution!(usize);
-from_for_advent_solution!(&str);
+from_for_advent_solution!(i32, i64);
+from_for_advent_solution!(i64, i64);
+from_for_advent_solution!(u64, u64);
+from_for_advent_solution!(usize, u64);
+
+impl From<BigInt> for AdventSolution {
+    fn from(x: BigInt) -> Self {
+        AdventSolution::Integer(x)
+    }
+}
+
+impl From<BigUint> for AdventSolution {
+    fn from(x: BigUint) -> Self {
+        AdventSolution::Integer(BigInt::from(x))
+    }
+}
+
+impl From<&str> for AdventSolution {
+    fn from(s: &str) -> Self {
+        AdventSolution::Text(s.to_string())
+    }
+}
 
 impl From<String> for AdventSolution {
     fn from(s: String) -> Self {
-        AdventSolution(s)
+        AdventSolution::Text(s)
+    }
+}
+
+/// Trailing whitespace in a display answer isn't significant, so strip it from
+/// each line and drop any blank lines at the end.
+fn normalize_display(grid: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = grid.lines().map(|l| l.trim_end()).collect();
+    while lines.last() == Some(&"") {
+        lines.pop();
     }
+    lines
 }
 
+impl AdventSolution {
+    /// Serialize as `{"type": ..., "value": ...}`. Integers are written as
+    /// JSON numbers however large they are.
+    pub fn to_json(&self) -> Json {
+        let (kind, value) = match self {
+            AdventSolution::Integer(x) => ("integer", Json::Number(x.to_string())),
+            AdventSolution::Text(s) => ("text", Json::String(s.clone())),
+            AdventSolution::Display(s) => ("display", Json::String(s.clone())),
+        };
+        Json::Object(vec![
+            ("type".to_string(), Json::String(kind.to_string())),
+            ("value".to_string(), value),
+        ])
+    }
+
+    pub fn from_json(json: &Json) -> Result<Self, String> {
+        let kind = json
+            .get("type")
+            .and_then(Json::as_str)
+            .ok_or("Solution JSON must have a string 'type'")?;
+        let value = json
+            .get("value")
+            .ok_or("Solution JSON must have a 'value'")?;
+        match (kind, value) {
+            ("integer", Json::Number(n)) => {
+                Ok(AdventSolution::Integer(n.parse().map_err(|_| {
+                    format!("Failed to parse integer solution '{}'", n)
+                })?))
+            }
+            ("text", Json::String(s)) => Ok(AdventSolution::Text(s.clone())),
+            ("display", Json::String(s)) => Ok(AdventSolution::Display(s.clone())),
+            (kind, value) => Err(format!(
+                "Found bad solution of type '{}' with value '{}'",
+                kind, value
+            )),
+        }
+    }
+}
+
+impl PartialEq for AdventSolution {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (AdventSolution::Integer(a), AdventSolution::Integer(b)) => a == b,
+            (AdventSolution::Text(a), AdventSolution::Text(b)) => a == b,
+            (AdventSolution::Display(a), AdventSolution::Display(b)) => {
+                normalize_display(a) == normalize_display(b)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for AdventSolution {}
+
 impl Display for AdventSolution {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        match self {
+            AdventSolution::Integer(x) => write!(f, "{}", x),
+            AdventSolution::Text(s) => write!(f, "{}", s),
+            AdventSolution::Display(s) => write!(f, "{}", s.trim_end_matches('\n')),
+        }
     }
 }
 
 pub type AdventResult = Result<AdventSolution, String>;
 
+/// A solver that reads its input as it goes instead of all at once, so that it
+/// can handle inputs that don't fit in memory.
+pub type StreamingSolver = fn(&mut dyn BufRead) -> AdventResult;
+
+/// Read the input one line at a time. Lines may end with `\n` or `\r\n`.
+pub fn read_lines(input: &mut dyn BufRead) -> impl Iterator<Item = Result<String, String>> + '_ {
+    input
+        .lines()
+        .map(|line| line.map_err(|e| format!("Failed to read input: {}", e)))
+}
+
 pub fn not_solved_yet(year: u16, day: u8, part: u8) -> AdventResult {
     Err(format!(
         "{} day {} part {} isn't solved yet",
@@ -40,6 +163,64 @@ pub fn not_solved_yet(year: u16, day: u8, part: u8) -> AdventResult {
     ))
 }
 
+/// Extra settings for solvers that can be tuned beyond what the puzzle asks,
+/// given on the command line as `--param KEY=VALUE`.
+#[derive(Clone, Debug, Default)]
+pub struct Params(BTreeMap<String, String>);
+
+impl Params {
+    pub fn parse<'a>(params: impl Iterator<Item = &'a str>) -> Result<Self, String> {
+        let mut map = BTreeMap::new();
+        for param in params {
+            match param.split_once('=') {
+                Some((key, value)) if !key.is_empty() => {
+                    map.insert(key.to_string(), value.to_string());
+                }
+                _ => {
+                    return Err(format!(
+                        "Parameters must look like KEY=VALUE (got '{}')",
+                        param
+                    ))
+                }
+            }
+        }
+        Ok(Params(map))
+    }
+
+    /// The value of a parameter, or `default` if it wasn't given.
+    pub fn get<T: FromStr>(&self, key: &str, default: T) -> Result<T, String> {
+        Ok(self.get_optional(key)?.unwrap_or(default))
+    }
+
+    /// The value of a parameter, or `None` if it wasn't given.
+    pub fn get_optional<T: FromStr>(&self, key: &str) -> Result<Option<T>, String> {
+        match self.0.get(key) {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("Failed to parse parameter {}='{}'", key, value)),
+            None => Ok(None),
+        }
+    }
+
+    /// Make sure that every parameter given is one of the `accepted` keys, so
+    /// that a typo (or a solver that takes no parameters) isn't ignored.
+    pub fn check_keys(&self, accepted: &[&str]) -> Result<(), String> {
+        match self.0.keys().find(|key| !accepted.contains(&key.as_str())) {
+            Some(key) if accepted.is_empty() => Err(format!(
+                "This solver doesn't take parameters (got '{}')",
+                key
+            )),
+            Some(key) => Err(format!(
+                "Unknown parameter '{}' (expected one of: {})",
+                key,
+                accepted.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
 #[cfg(test)]
 pub fn check_solution<T>(input: &str, output: T, solution: &dyn Fn(&str) -> AdventResult)
 where
@@ -51,9 +232,146 @@ where
     assert_eq!(solution_result.unwrap(), correct);
 }
 
+#[cfg(test)]
+pub fn check_streaming_solution<T>(
+    input: &str,
+    output: T,
+    solution: &dyn Fn(&mut dyn BufRead) -> AdventResult,
+) where
+    T: Into<AdventSolution>,
+{
+    // Use a tiny buffer so that lines get split across reads.
+    let mut reader = std::io::BufReader::with_capacity(3, input.as_bytes());
+    let solution_result = solution(&mut reader);
+    assert!(solution_result.is_ok());
+    let correct = output.into();
+    assert_eq!(solution_result.unwrap(), correct);
+}
+
+/// A reader that returns `data` and then fails, like a file on a disk that
+/// goes away partway through.
+#[cfg(test)]
+pub struct FailingReader<'a> {
+    pub data: &'a [u8],
+}
+
+#[cfg(test)]
+impl std::io::Read for FailingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.data.is_empty() {
+            return Err(std::io::Error::other("disk on fire"));
+        }
+        let n = buf.len().min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
 pub fn parse_u64(input: &str) -> Vec<u64> {
     input
         .split_terminator('\n')
         .map(|x| x.parse::<u64>().unwrap())
         .collect()
 }
+
+#[test]
+fn test_advent_solution_equality() {
+    assert_eq!(AdventSolution::from(42_u64), AdventSolution::from(42_i32));
+    assert_ne!(AdventSolution::from(42), AdventSolution::from("42"));
+    assert_eq!(
+        AdventSolution::Display("#..#\n#..#\n".to_string()),
+        AdventSolution::Display("#..#  \n#..#\n\n".to_string())
+    );
+    assert_ne!(
+        AdventSolution::Display("#..#\n#..#".to_string()),
+        AdventSolution::Display("#..#\n.#.#".to_string())
+    );
+    assert_ne!(
+        AdventSolution::Display("#..#".to_string()),
+        AdventSolution::from("#..#")
+    );
+}
+
+#[test]
+fn test_advent_solution_json() {
+    let solutions = [
+        AdventSolution::from(-7),
+        AdventSolution::from(BigUint::from(u64::MAX).pow(3)),
+        AdventSolution::from("CMZ"),
+        AdventSolution::Display("#.\n.#\n".to_string()),
+    ];
+    for solution in solutions {
+        let json = json::parse_json(&solution.to_json().to_string()).unwrap();
+        let parsed = AdventSolution::from_json(&json).unwrap();
+        assert_eq!(parsed, solution);
+        assert_eq!(parsed.to_json(), solution.to_json());
+    }
+    assert_eq!(
+        AdventSolution::from(26984457539_u64).to_json().to_string(),
+        "{\"type\":\"integer\",\"value\":26984457539}"
+    );
+    for bad in [
+        "{\"type\":\"integer\",\"value\":\"12\"}",
+        "{\"type\":\"integer\",\"value\":1.5}",
+        "{\"type\":\"picture\",\"value\":\"\"}",
+        "{\"value\":1}",
+    ] {
+        assert!(AdventSolution::from_json(&json::parse_json(bad).unwrap()).is_err());
+    }
+}
+
+#[test]
+fn test_read_lines() {
+    let mut input = std::io::BufReader::with_capacity(2, "a\r\nbc\n\nd".as_bytes());
+    let lines: Result<Vec<String>, String> = read_lines(&mut input).collect();
+    assert_eq!(
+        lines,
+        Ok(vec!["a".into(), "bc".into(), "".into(), "d".into()])
+    );
+
+    let mut input = std::io::BufReader::new(FailingReader { data: b"a\nb" });
+    let mut lines = read_lines(&mut input);
+    assert_eq!(lines.next(), Some(Ok("a".to_string())));
+    assert_eq!(
+        lines.next(),
+        Some(Err("Failed to read input: disk on fire".to_string()))
+    );
+}
+
+#[test]
+fn test_streaming_solver_read_error() {
+    let solvers: [StreamingSolver; 4] = [
+        crate::solve_2021::day_01::part_1,
+        crate::solve_2021::day_02::part_2,
+        crate::solve_2020::day_02::part_1,
+        crate::solve_2022::day_01::part_2,
+    ];
+    for solver in solvers {
+        let mut input = std::io::BufReader::new(FailingReader { data: b"" });
+        assert_eq!(
+            solver(&mut input),
+            Err("Failed to read input: disk on fire".to_string())
+        );
+    }
+}
+
+#[test]
+fn test_params() {
+    let params = Params::parse(["target=99", "k=4", "name=a=b"].into_iter()).unwrap();
+    assert_eq!(params.get("target", 2020_u64), Ok(99));
+    assert_eq!(params.get("k", 2_usize), Ok(4));
+    assert_eq!(params.get("missing", 7_u8), Ok(7));
+    assert_eq!(params.get("name", String::new()), Ok("a=b".to_string()));
+    assert!(params.get("name", 0_u64).is_err());
+    assert!(Params::parse(["target"].into_iter()).is_err());
+    assert!(Params::parse(["=5"].into_iter()).is_err());
+
+    assert_eq!(params.check_keys(&["k", "name", "target"]), Ok(()));
+    assert_eq!(
+        params.check_keys(&["k", "target"]),
+        Err("Unknown parameter 'name' (expected one of: k, target)".to_string())
+    );
+    assert!(params.check_keys(&[]).is_err());
+    assert_eq!(Params::default().check_keys(&[]), Ok(()));
+}
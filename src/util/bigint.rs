@@ -0,0 +1,659 @@
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use std::str::FromStr;
+
+/// An arbitrarily large non-negative integer.
+///
+/// The value is stored as base 2^32 digits ("limbs"), least significant first,
+/// with no trailing zero limbs. Zero has no limbs at all.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+/// An arbitrarily large integer, stored as a sign and a magnitude.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: BigUint,
+}
+
+/// The largest power of ten that fits in a limb, used to convert to and from
+/// decimal nine digits at a time.
+const DECIMAL_CHUNK: u32 = 1_000_000_000;
+
+impl BigUint {
+    pub fn zero() -> Self {
+        BigUint { limbs: Vec::new() }
+    }
+
+    pub fn one() -> Self {
+        BigUint { limbs: vec![1] }
+    }
+
+    fn from_limbs(mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigUint { limbs }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// The number of bits needed to write the value in binary.
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    /// The value as a `u64`, if it fits.
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs[..] {
+            [] => Some(0),
+            [a] => Some(a as u64),
+            [a, b] => Some(a as u64 | (b as u64) << 32),
+            _ => None,
+        }
+    }
+
+    pub fn checked_sub(&self, other: &BigUint) -> Option<BigUint> {
+        if *self < *other {
+            return None;
+        }
+        let mut limbs = Vec::with_capacity(self.limbs.len());
+        let mut borrow = 0;
+        for (idx, a) in self.limbs.iter().enumerate() {
+            let b = other.limbs.get(idx).copied().unwrap_or(0);
+            let (difference, borrowed_1) = a.overflowing_sub(b);
+            let (difference, borrowed_2) = difference.overflowing_sub(borrow);
+            limbs.push(difference);
+            borrow = (borrowed_1 || borrowed_2) as u32;
+        }
+        Some(BigUint::from_limbs(limbs))
+    }
+
+    pub fn pow(&self, exponent: u64) -> BigUint {
+        let mut result = BigUint::one();
+        let mut base = self.clone();
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    /// Divide by a single limb, returning the quotient and remainder.
+    fn div_rem_small(&self, divisor: u32) -> (BigUint, u32) {
+        let mut quotient = vec![0; self.limbs.len()];
+        let mut remainder = 0u64;
+        for (idx, limb) in self.limbs.iter().enumerate().rev() {
+            let current = remainder << 32 | *limb as u64;
+            quotient[idx] = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+        (BigUint::from_limbs(quotient), remainder as u32)
+    }
+
+    /// Divide with the remainder, using Knuth's algorithm D.
+    ///
+    /// Panics if `divisor` is zero.
+    pub fn div_rem(&self, divisor: &BigUint) -> (BigUint, BigUint) {
+        assert!(!divisor.is_zero(), "attempt to divide by zero");
+        if *self < *divisor {
+            return (BigUint::zero(), self.clone());
+        }
+        if divisor.limbs.len() == 1 {
+            let (quotient, remainder) = self.div_rem_small(divisor.limbs[0]);
+            return (quotient, BigUint::from(remainder as u64));
+        }
+
+        // Shift both numbers so that the divisor's top limb has its high bit
+        // set, which keeps each estimated quotient digit within two of the
+        // real one.
+        let shift = divisor.limbs.last().unwrap().leading_zeros();
+        let v = shift_left(&divisor.limbs, shift);
+        let mut u = shift_left(&self.limbs, shift);
+        if u.len() == self.limbs.len() {
+            u.push(0);
+        }
+        let n = v.len();
+        let m = u.len() - n - 1;
+        let base = 1u64 << 32;
+        let mut quotient = vec![0u32; m + 1];
+        for j in (0..=m).rev() {
+            // Estimate the next quotient digit from the top two limbs, then
+            // correct it using the third.
+            let top = (u[j + n] as u64) << 32 | u[j + n - 1] as u64;
+            let mut q_hat = top / v[n - 1] as u64;
+            let mut r_hat = top % v[n - 1] as u64;
+            while q_hat >= base || q_hat * v[n - 2] as u64 > (r_hat << 32 | u[j + n - 2] as u64) {
+                q_hat -= 1;
+                r_hat += v[n - 1] as u64;
+                if r_hat >= base {
+                    break;
+                }
+            }
+
+            // Subtract q_hat times the divisor from the current window.
+            let mut borrow = 0i64;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let product = q_hat * v[i] as u64 + carry;
+                carry = product >> 32;
+                let t = u[i + j] as i64 - borrow - (product & 0xffff_ffff) as i64;
+                u[i + j] = t as u32;
+                borrow = (t < 0) as i64;
+            }
+            let t = u[j + n] as i64 - borrow - carry as i64;
+            u[j + n] = t as u32;
+
+            // The estimate was still one too large, so add the divisor back.
+            if t < 0 {
+                q_hat -= 1;
+                let mut carry = 0u64;
+                for i in 0..n {
+                    let sum = u[i + j] as u64 + v[i] as u64 + carry;
+                    u[i + j] = sum as u32;
+                    carry = sum >> 32;
+                }
+                u[j + n] = u[j + n].wrapping_add(carry as u32);
+            }
+            quotient[j] = q_hat as u32;
+        }
+        u.truncate(n);
+        let remainder = shift_right(&u, shift);
+        (
+            BigUint::from_limbs(quotient),
+            BigUint::from_limbs(remainder),
+        )
+    }
+}
+
+fn shift_left(limbs: &[u32], shift: u32) -> Vec<u32> {
+    if shift == 0 {
+        return limbs.to_vec();
+    }
+    let mut shifted = Vec::with_capacity(limbs.len() + 1);
+    let mut carry = 0;
+    for limb in limbs {
+        shifted.push(limb << shift | carry);
+        carry = limb >> (32 - shift);
+    }
+    if carry != 0 {
+        shifted.push(carry);
+    }
+    shifted
+}
+
+fn shift_right(limbs: &[u32], shift: u32) -> Vec<u32> {
+    if shift == 0 {
+        return limbs.to_vec();
+    }
+    let mut shifted = vec![0; limbs.len()];
+    for idx in 0..limbs.len() {
+        let high = limbs.get(idx + 1).map_or(0, |l| l << (32 - shift));
+        shifted[idx] = limbs[idx] >> shift | high;
+    }
+    shifted
+}
+
+impl From<u64> for BigUint {
+    fn from(x: u64) -> Self {
+        BigUint::from_limbs(vec![x as u32, (x >> 32) as u32])
+    }
+}
+
+impl From<usize> for BigUint {
+    fn from(x: usize) -> Self {
+        BigUint::from(x as u64)
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("Invalid integer: '{}'", s));
+        }
+        // Read nine digits at a time, with the first chunk taking whatever's
+        // left over.
+        let mut result = BigUint::zero();
+        let first_len = match s.len() % 9 {
+            0 => 9,
+            l => l,
+        };
+        let mut start = 0;
+        let mut end = first_len;
+        while start < s.len() {
+            let chunk = s[start..end].parse::<u32>().unwrap();
+            result =
+                &(&result * &BigUint::from(DECIMAL_CHUNK as u64)) + &BigUint::from(chunk as u64);
+            start = end;
+            end += 9;
+        }
+        Ok(result)
+    }
+}
+
+impl Display for BigUint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return f.pad_integral(true, "", "0");
+        }
+        let mut chunks = Vec::new();
+        let mut remaining = self.clone();
+        while !remaining.is_zero() {
+            let (quotient, chunk) = remaining.div_rem_small(DECIMAL_CHUNK);
+            chunks.push(chunk);
+            remaining = quotient;
+        }
+        let mut digits = chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{:09}", chunk));
+        }
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+impl Debug for BigUint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+    fn add(self, other: &BigUint) -> BigUint {
+        let (long, short) = if self.limbs.len() >= other.limbs.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut limbs = Vec::with_capacity(long.limbs.len() + 1);
+        let mut carry = 0u64;
+        for (idx, a) in long.limbs.iter().enumerate() {
+            let sum = *a as u64 + short.limbs.get(idx).copied().unwrap_or(0) as u64 + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        limbs.push(carry as u32);
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Sub for &BigUint {
+    type Output = BigUint;
+    fn sub(self, other: &BigUint) -> BigUint {
+        self.checked_sub(other)
+            .expect("attempt to subtract with overflow")
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+    fn mul(self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let product = *a as u64 * *b as u64 + limbs[i + j] as u64 + carry;
+                limbs[i + j] = product as u32;
+                carry = product >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Div for &BigUint {
+    type Output = BigUint;
+    fn div(self, other: &BigUint) -> BigUint {
+        self.div_rem(other).0
+    }
+}
+
+impl Rem for &BigUint {
+    type Output = BigUint;
+    fn rem(self, other: &BigUint) -> BigUint {
+        self.div_rem(other).1
+    }
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        BigInt::default()
+    }
+
+    fn new(negative: bool, magnitude: BigUint) -> Self {
+        BigInt {
+            // There's only one zero.
+            negative: negative && !magnitude.is_zero(),
+            magnitude,
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn magnitude(&self) -> &BigUint {
+        &self.magnitude
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        let magnitude = self.magnitude.to_u64()?;
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    pub fn pow(&self, exponent: u64) -> BigInt {
+        BigInt::new(
+            self.negative && exponent % 2 == 1,
+            self.magnitude.pow(exponent),
+        )
+    }
+
+    /// Divide with the remainder. Like Rust's built-in integers, the quotient
+    /// is rounded toward zero, and the remainder has the sign of `self`.
+    ///
+    /// Panics if `divisor` is zero.
+    pub fn div_rem(&self, divisor: &BigInt) -> (BigInt, BigInt) {
+        let (quotient, remainder) = self.magnitude.div_rem(&divisor.magnitude);
+        (
+            BigInt::new(self.negative != divisor.negative, quotient),
+            BigInt::new(self.negative, remainder),
+        )
+    }
+}
+
+impl From<BigUint> for BigInt {
+    fn from(x: BigUint) -> Self {
+        BigInt::new(false, x)
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(x: i64) -> Self {
+        BigInt::new(x < 0, BigUint::from(x.unsigned_abs()))
+    }
+}
+
+impl From<u64> for BigInt {
+    fn from(x: u64) -> Self {
+        BigInt::new(false, BigUint::from(x))
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix('-') {
+            Some(digits) => Ok(BigInt::new(true, digits.parse()?)),
+            None => Ok(BigInt::new(false, s.parse()?)),
+        }
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad_integral(!self.negative, "", &self.magnitude.to_string())
+    }
+}
+
+impl Debug for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, &self.magnitude + &other.magnitude);
+        }
+        // The signs differ, so subtract the smaller magnitude from the larger.
+        match self.magnitude.cmp(&other.magnitude) {
+            Ordering::Less => BigInt::new(other.negative, &other.magnitude - &self.magnitude),
+            _ => BigInt::new(self.negative, &self.magnitude - &other.magnitude),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::new(
+            self.negative != other.negative,
+            &self.magnitude * &other.magnitude,
+        )
+    }
+}
+
+impl Div for &BigInt {
+    type Output = BigInt;
+    fn div(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).0
+    }
+}
+
+impl Rem for &BigInt {
+    type Output = BigInt;
+    fn rem(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).1
+    }
+}
+
+/// Implement an operator on owned values by borrowing them.
+macro_rules! forward_owned_binop {
+    ($t: ty, $imp: ident, $method: ident) => {
+        impl $imp for $t {
+            type Output = $t;
+            fn $method(self, other: $t) -> $t {
+                (&self).$method(&other)
+            }
+        }
+    };
+}
+
+forward_owned_binop!(BigUint, Add, add);
+forward_owned_binop!(BigUint, Sub, sub);
+forward_owned_binop!(BigUint, Mul, mul);
+forward_owned_binop!(BigUint, Div, div);
+forward_owned_binop!(BigUint, Rem, rem);
+forward_owned_binop!(BigInt, Add, add);
+forward_owned_binop!(BigInt, Sub, sub);
+forward_owned_binop!(BigInt, Mul, mul);
+forward_owned_binop!(BigInt, Div, div);
+forward_owned_binop!(BigInt, Rem, rem);
+
+impl Neg for BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        -&self
+    }
+}
+
+#[cfg(test)]
+fn big(s: &str) -> BigUint {
+    s.parse().unwrap()
+}
+
+/// A small pseudo-random number generator, so the tests can cover lots of
+/// values without a dependency.
+#[cfg(test)]
+fn test_values() -> Vec<u128> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut values = vec![0, 1, u32::MAX as u128, u64::MAX as u128, u128::MAX];
+    for _ in 0..200 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        // Vary the size so that every limb count shows up.
+        let bits = state % 128;
+        values.push(((state as u128) << 64 | state.rotate_left(23) as u128) >> bits);
+    }
+    values
+}
+
+#[cfg(test)]
+fn from_u128(x: u128) -> BigUint {
+    big(&x.to_string())
+}
+
+#[test]
+fn test_biguint_formatting() {
+    assert_eq!(BigUint::zero().to_string(), "0");
+    assert_eq!(BigUint::from(1_000_000_000_u64).to_string(), "1000000000");
+    assert_eq!(big("000123").to_string(), "123");
+    assert_eq!(format!("{:>6}", big("42")), "    42");
+    assert!("12a".parse::<BigUint>().is_err());
+    assert!("".parse::<BigUint>().is_err());
+    for x in test_values() {
+        assert_eq!(from_u128(x).to_string(), x.to_string());
+    }
+}
+
+#[test]
+fn test_biguint_arithmetic() {
+    let values = test_values();
+    for a in values.iter() {
+        for b in values.iter().step_by(7) {
+            let (x, y) = (from_u128(*a), from_u128(*b));
+            if let Some(sum) = a.checked_add(*b) {
+                assert_eq!(&x + &y, from_u128(sum));
+            }
+            if let Some(product) = a.checked_mul(*b) {
+                assert_eq!(&x * &y, from_u128(product));
+            }
+            assert_eq!(x.checked_sub(&y), a.checked_sub(*b).map(from_u128));
+            assert_eq!(x.cmp(&y), a.cmp(b));
+            if *b != 0 {
+                assert_eq!(x.div_rem(&y), (from_u128(a / b), from_u128(a % b)));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_biguint_large_values() {
+    let two_to_the_200 = BigUint::from(2_u64).pow(200);
+    assert_eq!(
+        two_to_the_200.to_string(),
+        "1606938044258990275541962092341162602522202993782792835301376"
+    );
+    assert_eq!(two_to_the_200.bits(), 201);
+
+    let factorial = (1..=100_u64).fold(BigUint::one(), |acc, n| acc * BigUint::from(n));
+    assert_eq!(
+        factorial.to_string(),
+        "93326215443944152681699238856266700490715968264381621468592963895217599993229915608941463976156518286253697920827223758251185210916864000000000000000000000000"
+    );
+    // Division by large divisors needs the full long division algorithm.
+    let divisor = big("340282366920938463463374607431768211507");
+    let (quotient, remainder) = factorial.div_rem(&divisor);
+    assert!(remainder < divisor);
+    assert_eq!(&(&quotient * &divisor) + &remainder, factorial);
+    assert_eq!(&factorial / &two_to_the_200.pow(0), factorial);
+    for values in test_values().chunks_exact(3) {
+        let dividend = values
+            .iter()
+            .fold(BigUint::one(), |acc, v| acc * from_u128(*v | 1));
+        let divisor = from_u128(values[0] | 1) * from_u128(values[1] | 3);
+        let (quotient, remainder) = dividend.div_rem(&divisor);
+        assert!(remainder < divisor);
+        assert_eq!(&(&quotient * &divisor) + &remainder, dividend);
+    }
+}
+
+#[test]
+fn test_bigint() {
+    let values = [-1_000_000_007_i64, -12, -1, 0, 1, 5, 12, 1 << 40, i64::MAX];
+    for a in values {
+        for b in values {
+            let (x, y) = (BigInt::from(a), BigInt::from(b));
+            let (a, b) = (a as i128, b as i128);
+            assert_eq!((&x + &y).to_string(), (a + b).to_string());
+            assert_eq!((&x - &y).to_string(), (a - b).to_string());
+            assert_eq!((&x * &y).to_string(), (a * b).to_string());
+            assert_eq!(x.cmp(&y), a.cmp(&b));
+            if b != 0 {
+                assert_eq!((&x / &y).to_string(), (a / b).to_string());
+                assert_eq!((&x % &y).to_string(), (a % b).to_string());
+            }
+        }
+    }
+    assert_eq!(BigInt::from(-3_i64).pow(3), BigInt::from(-27_i64));
+    assert_eq!(BigInt::from(-3_i64).pow(4), BigInt::from(81_i64));
+    assert_eq!("-0".parse::<BigInt>(), Ok(BigInt::zero()));
+    assert_eq!("-42".parse::<BigInt>().unwrap().to_i64(), Some(-42));
+    assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+    assert_eq!(
+        (BigInt::from(i64::MIN) - BigInt::from(1_i64)).to_i64(),
+        None
+    );
+}
@@ -0,0 +1,356 @@
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
+
+const WORD_BITS: usize = 64;
+
+/// A fixed-size set of small integers, stored one bit per possible member.
+///
+/// Every set has a capacity, chosen when it's created, and only values below
+/// the capacity can be members. Combining two sets with different capacities
+/// panics.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+    capacity: usize,
+}
+
+impl BitSet {
+    pub fn new(capacity: usize) -> Self {
+        BitSet {
+            words: vec![0; capacity.div_ceil(WORD_BITS)],
+            capacity,
+        }
+    }
+
+    /// A set containing every value below `capacity`.
+    pub fn full(capacity: usize) -> Self {
+        let mut set = BitSet {
+            words: vec![u64::MAX; capacity.div_ceil(WORD_BITS)],
+            capacity,
+        };
+        set.clear_unused_bits();
+        set
+    }
+
+    /// Bits past the capacity in the last word are always zero, so that
+    /// counting and comparing sets can work a word at a time.
+    fn clear_unused_bits(&mut self) {
+        let used = self.capacity % WORD_BITS;
+        if used != 0 {
+            if let Some(last) = self.words.last_mut() {
+                *last &= (1 << used) - 1;
+            }
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn locate(&self, value: usize) -> (usize, u64) {
+        assert!(
+            value < self.capacity,
+            "{} is out of range for a set with capacity {}",
+            value,
+            self.capacity
+        );
+        (value / WORD_BITS, 1 << (value % WORD_BITS))
+    }
+
+    /// Add a value, returning whether it was newly added.
+    pub fn insert(&mut self, value: usize) -> bool {
+        let (word, bit) = self.locate(value);
+        let added = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        added
+    }
+
+    /// Remove a value, returning whether it was present.
+    pub fn remove(&mut self, value: usize) -> bool {
+        let (word, bit) = self.locate(value);
+        let present = self.words[word] & bit != 0;
+        self.words[word] &= !bit;
+        present
+    }
+
+    pub fn set(&mut self, value: usize, present: bool) {
+        if present {
+            self.insert(value);
+        } else {
+            self.remove(value);
+        }
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        let (word, bit) = self.locate(value);
+        self.words[word] & bit != 0
+    }
+
+    /// The number of members (the population count).
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = 0);
+    }
+
+    /// Every value below the capacity that isn't in this set.
+    pub fn complement(&self) -> BitSet {
+        let mut set = BitSet {
+            words: self.words.iter().map(|w| !w).collect(),
+            capacity: self.capacity,
+        };
+        set.clear_unused_bits();
+        set
+    }
+
+    pub fn is_subset(&self, other: &BitSet) -> bool {
+        self.check_capacity(other);
+        self.words
+            .iter()
+            .zip(&other.words)
+            .all(|(a, b)| a & !b == 0)
+    }
+
+    pub fn is_superset(&self, other: &BitSet) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &BitSet) -> bool {
+        self.check_capacity(other);
+        self.words.iter().zip(&other.words).all(|(a, b)| a & b == 0)
+    }
+
+    fn check_capacity(&self, other: &BitSet) {
+        assert_eq!(
+            self.capacity, other.capacity,
+            "sets must have the same capacity"
+        );
+    }
+
+    fn combine(&self, other: &BitSet, op: impl Fn(u64, u64) -> u64) -> BitSet {
+        self.check_capacity(other);
+        BitSet {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(a, b)| op(*a, *b))
+                .collect(),
+            capacity: self.capacity,
+        }
+    }
+
+    /// Iterate over the members in increasing order.
+    pub fn iter(&self) -> Ones<'_> {
+        Ones {
+            words: &self.words,
+            word_idx: 0,
+            current: self.words.first().copied().unwrap_or(0),
+        }
+    }
+}
+
+impl BitAnd for &BitSet {
+    type Output = BitSet;
+    fn bitand(self, other: &BitSet) -> BitSet {
+        self.combine(other, |a, b| a & b)
+    }
+}
+
+impl BitOr for &BitSet {
+    type Output = BitSet;
+    fn bitor(self, other: &BitSet) -> BitSet {
+        self.combine(other, |a, b| a | b)
+    }
+}
+
+impl BitXor for &BitSet {
+    type Output = BitSet;
+    fn bitxor(self, other: &BitSet) -> BitSet {
+        self.combine(other, |a, b| a ^ b)
+    }
+}
+
+impl Sub for &BitSet {
+    type Output = BitSet;
+    fn sub(self, other: &BitSet) -> BitSet {
+        self.combine(other, |a, b| a & !b)
+    }
+}
+
+/// An iterator over the members of a [`BitSet`], which skips over empty words
+/// and finds each member with a trailing zero count.
+pub struct Ones<'a> {
+    words: &'a [u64],
+    word_idx: usize,
+    current: u64,
+}
+
+impl Iterator for Ones<'_> {
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        while self.current == 0 {
+            self.word_idx += 1;
+            self.current = *self.words.get(self.word_idx)?;
+        }
+        let bit = self.current.trailing_zeros() as usize;
+        // Clear the lowest set bit.
+        self.current &= self.current - 1;
+        Some(self.word_idx * WORD_BITS + bit)
+    }
+}
+
+impl<'a> IntoIterator for &'a BitSet {
+    type Item = usize;
+    type IntoIter = Ones<'a>;
+    fn into_iter(self) -> Ones<'a> {
+        self.iter()
+    }
+}
+
+/// A rectangular grid of bits, stored row by row in a [`BitSet`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Bitboard {
+    width: usize,
+    height: usize,
+    cells: BitSet,
+}
+
+impl Bitboard {
+    pub fn new(width: usize, height: usize) -> Self {
+        Bitboard {
+            width,
+            height,
+            cells: BitSet::new(width * height),
+        }
+    }
+
+    /// The position of a cell in the underlying [`BitSet`].
+    pub fn index(&self, row: usize, col: usize) -> usize {
+        assert!(row < self.height && col < self.width);
+        row * self.width + col
+    }
+
+    #[allow(dead_code)]
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        self.cells.contains(self.index(row, col))
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        let idx = self.index(row, col);
+        self.cells.set(idx, value);
+    }
+
+    pub fn cells(&self) -> &BitSet {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut BitSet {
+        &mut self.cells
+    }
+
+    #[allow(dead_code)]
+    pub fn count(&self) -> usize {
+        self.cells.count()
+    }
+}
+
+/// Masks covering each full row and column of a board, computed once so that
+/// checking for a complete line is one AND per line.
+#[derive(Clone, Debug)]
+pub struct LineMasks {
+    pub rows: Vec<BitSet>,
+    pub cols: Vec<BitSet>,
+}
+
+impl LineMasks {
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut board = Bitboard::new(width, height);
+        let rows = (0..height)
+            .map(|row| {
+                board.cells.clear();
+                (0..width).for_each(|col| board.set(row, col, true));
+                board.cells.clone()
+            })
+            .collect();
+        let cols = (0..width)
+            .map(|col| {
+                board.cells.clear();
+                (0..height).for_each(|row| board.set(row, col, true));
+                board.cells.clone()
+            })
+            .collect();
+        LineMasks { rows, cols }
+    }
+
+    /// Whether every cell in some row or column is set.
+    pub fn has_complete_line(&self, board: &Bitboard) -> bool {
+        self.rows
+            .iter()
+            .chain(self.cols.iter())
+            .any(|mask| mask.is_subset(board.cells()))
+    }
+}
+
+#[test]
+fn test_bitset() {
+    let mut set = BitSet::new(130);
+    assert!(set.is_empty());
+    for value in [0, 5, 63, 64, 129] {
+        assert!(set.insert(value));
+    }
+    assert!(!set.insert(5));
+    assert_eq!(set.count(), 5);
+    assert!(set.contains(63) && set.contains(64) && !set.contains(65));
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![0, 5, 63, 64, 129]);
+    assert!(set.remove(63));
+    assert!(!set.remove(63));
+    assert_eq!(set.complement().count(), 130 - 4);
+    assert_eq!(BitSet::full(130), &set | &set.complement());
+    assert!((&set & &set.complement()).is_empty());
+    assert_eq!(BitSet::new(0).iter().next(), None);
+}
+
+#[test]
+fn test_bitset_operations() {
+    let mut evens = BitSet::new(100);
+    let mut threes = BitSet::new(100);
+    (0..100).step_by(2).for_each(|v| evens.set(v, true));
+    (0..100).step_by(3).for_each(|v| threes.set(v, true));
+    let sixes: Vec<usize> = (0..100).step_by(6).collect();
+    assert_eq!((&evens & &threes).iter().collect::<Vec<_>>(), sixes);
+    assert_eq!((&evens | &threes).count(), 50 + 34 - 17);
+    assert_eq!((&evens ^ &threes).count(), 50 + 34 - 2 * 17);
+    assert_eq!((&evens - &threes).count(), 50 - 17);
+    assert!((&evens & &threes).is_subset(&evens));
+    assert!(evens.is_superset(&(&evens & &threes)));
+    assert!(evens.is_disjoint(&evens.complement()));
+    assert!(!evens.is_disjoint(&threes));
+}
+
+#[test]
+fn test_bitboard_lines() {
+    let masks = LineMasks::new(5, 3);
+    let mut board = Bitboard::new(5, 3);
+    for col in 0..4 {
+        board.set(1, col, true);
+    }
+    assert!(!masks.has_complete_line(&board));
+    board.set(1, 4, true);
+    assert!(masks.has_complete_line(&board));
+    assert_eq!(masks.rows[1], *board.cells());
+
+    let mut board = Bitboard::new(5, 3);
+    board.set(0, 2, true);
+    board.set(2, 2, true);
+    assert!(!masks.has_complete_line(&board));
+    board.set(1, 2, true);
+    assert!(masks.has_complete_line(&board));
+    assert_eq!(board.count(), 3);
+    assert!(board.get(1, 2) && !board.get(1, 3));
+}
@@ -0,0 +1,275 @@
+//! Iterators over ways of choosing and arranging the items in a slice.
+//!
+//! Each iterator keeps its position as a handful of indices and only allocates
+//! the `Vec` of references that it yields.
+
+/// The number of ways to choose `k` items from `n`, or `None` if it doesn't
+/// fit in a `u64`.
+#[allow(dead_code)]
+pub fn binomial(n: u64, k: u64) -> Option<u64> {
+    if k > n {
+        return Some(0);
+    }
+    let k = k.min(n - k);
+    // Each partial product is itself a binomial coefficient, so the division
+    // is always exact.
+    (0..k).try_fold(1_u64, |acc, i| {
+        let product = acc as u128 * (n - i) as u128 / (i + 1) as u128;
+        u64::try_from(product).ok()
+    })
+}
+
+/// `n!`, or `None` if it doesn't fit in a `u64`.
+#[allow(dead_code)]
+pub fn factorial(n: u64) -> Option<u64> {
+    (1..=n).try_fold(1_u64, |acc, i| acc.checked_mul(i))
+}
+
+/// Every way to choose `k` items from `items`, in lexicographic order of their
+/// positions. Each item is used at most once per combination.
+#[allow(dead_code)]
+pub fn combinations<T>(items: &[T], k: usize) -> Combinations<'_, T> {
+    Combinations {
+        items,
+        indices: (0..k).collect(),
+        done: k > items.len(),
+    }
+}
+
+#[allow(dead_code)]
+pub struct Combinations<'a, T> {
+    items: &'a [T],
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl<'a, T> Iterator for Combinations<'a, T> {
+    type Item = Vec<&'a T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let combination = self.indices.iter().map(|i| &self.items[*i]).collect();
+        // Advance the rightmost index that still has room to move, and pack
+        // the ones after it right behind it.
+        let (n, k) = (self.items.len(), self.indices.len());
+        match (0..k).rev().find(|i| self.indices[*i] < n - k + i) {
+            Some(i) => {
+                self.indices[i] += 1;
+                for j in i + 1..k {
+                    self.indices[j] = self.indices[j - 1] + 1;
+                }
+            }
+            None => self.done = true,
+        }
+        Some(combination)
+    }
+}
+
+/// Every ordering of `items`, generated with Heap's algorithm so that each
+/// permutation differs from the one before by a single swap.
+pub fn permutations<T>(items: &[T]) -> Permutations<'_, T> {
+    Permutations {
+        current: items.iter().collect(),
+        counters: vec![0; items.len()],
+        position: 0,
+        started: false,
+    }
+}
+
+pub struct Permutations<'a, T> {
+    current: Vec<&'a T>,
+    counters: Vec<usize>,
+    position: usize,
+    started: bool,
+}
+
+impl<'a, T> Iterator for Permutations<'a, T> {
+    type Item = Vec<&'a T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            return Some(self.current.clone());
+        }
+        while self.position < self.current.len() {
+            let i = self.position;
+            if self.counters[i] < i {
+                if i.is_multiple_of(2) {
+                    self.current.swap(0, i);
+                } else {
+                    self.current.swap(self.counters[i], i);
+                }
+                self.counters[i] += 1;
+                self.position = 0;
+                return Some(self.current.clone());
+            }
+            self.counters[i] = 0;
+            self.position += 1;
+        }
+        None
+    }
+}
+
+/// Every subset of `items`, including the empty set and `items` itself. The
+/// subsets count up in binary, with the first item as the lowest bit.
+///
+/// Panics if there are 64 or more items, since there would be too many subsets
+/// to ever finish.
+#[allow(dead_code)]
+pub fn power_set<T>(items: &[T]) -> PowerSet<'_, T> {
+    assert!(items.len() < 64, "too many items for a power set");
+    PowerSet {
+        items,
+        mask: 0,
+        end: 1 << items.len(),
+    }
+}
+
+#[allow(dead_code)]
+pub struct PowerSet<'a, T> {
+    items: &'a [T],
+    mask: u64,
+    end: u64,
+}
+
+impl<'a, T> Iterator for PowerSet<'a, T> {
+    type Item = Vec<&'a T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.mask == self.end {
+            return None;
+        }
+        let subset = (0..self.items.len())
+            .filter(|i| self.mask & (1 << i) != 0)
+            .map(|i| &self.items[i])
+            .collect();
+        self.mask += 1;
+        Some(subset)
+    }
+}
+
+/// Every way to pick one item from each slice, with the last slice changing
+/// fastest.
+#[allow(dead_code)]
+pub fn cartesian_product<'a, T>(slices: &[&'a [T]]) -> CartesianProduct<'a, T> {
+    CartesianProduct {
+        slices: slices.to_vec(),
+        indices: vec![0; slices.len()],
+        done: slices.iter().any(|s| s.is_empty()),
+    }
+}
+
+#[allow(dead_code)]
+pub struct CartesianProduct<'a, T> {
+    slices: Vec<&'a [T]>,
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl<'a, T> Iterator for CartesianProduct<'a, T> {
+    type Item = Vec<&'a T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let product = self
+            .indices
+            .iter()
+            .zip(&self.slices)
+            .map(|(i, s)| &s[*i])
+            .collect();
+        // Count up like an odometer.
+        self.done = true;
+        for (i, slice) in self.slices.iter().enumerate().rev() {
+            self.indices[i] += 1;
+            if self.indices[i] < slice.len() {
+                self.done = false;
+                break;
+            }
+            self.indices[i] = 0;
+        }
+        Some(product)
+    }
+}
+
+#[test]
+fn test_binomial_and_factorial() {
+    assert_eq!(binomial(5, 2), Some(10));
+    assert_eq!(binomial(5, 0), Some(1));
+    assert_eq!(binomial(2, 5), Some(0));
+    assert_eq!(binomial(67, 33), Some(14_226_520_737_620_288_370));
+    assert_eq!(binomial(70, 35), None);
+    assert_eq!(factorial(0), Some(1));
+    assert_eq!(factorial(20), Some(2_432_902_008_176_640_000));
+    assert_eq!(factorial(21), None);
+}
+
+#[test]
+fn test_combinations() {
+    let items = [1, 2, 3, 4];
+    let pairs: Vec<Vec<&i32>> = combinations(&items, 2).collect();
+    assert_eq!(
+        pairs,
+        vec![
+            vec![&1, &2],
+            vec![&1, &3],
+            vec![&1, &4],
+            vec![&2, &3],
+            vec![&2, &4],
+            vec![&3, &4]
+        ]
+    );
+    let items: Vec<u64> = (0..10).collect();
+    for k in 0..=11 {
+        assert_eq!(
+            combinations(&items, k as usize).count() as u64,
+            binomial(10, k).unwrap()
+        );
+    }
+    assert_eq!(combinations::<u8>(&[], 0).count(), 1);
+}
+
+#[test]
+fn test_permutations() {
+    let items = ['a', 'b', 'c'];
+    let mut all: Vec<String> = permutations(&items)
+        .map(|p| p.into_iter().collect())
+        .collect();
+    assert_eq!(all[0], "abc");
+    all.sort();
+    assert_eq!(all, vec!["abc", "acb", "bac", "bca", "cab", "cba"]);
+    let items: Vec<u64> = (0..7).collect();
+    for n in 0..=7 {
+        let mut seen: Vec<Vec<&u64>> = permutations(&items[..n as usize]).collect();
+        assert_eq!(seen.len() as u64, factorial(n).unwrap());
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len() as u64, factorial(n).unwrap());
+    }
+}
+
+#[test]
+fn test_power_set_and_product() {
+    let items = ["x", "y", "z"];
+    let subsets: Vec<Vec<&&str>> = power_set(&items).collect();
+    assert_eq!(subsets.len(), 8);
+    assert_eq!(subsets[0], Vec::<&&str>::new());
+    assert_eq!(subsets[5], vec![&"x", &"z"]);
+    let items: Vec<u8> = (0..12).collect();
+    assert_eq!(power_set(&items).count(), 1 << 12);
+    for k in 0..=12 {
+        assert_eq!(
+            power_set(&items).filter(|s| s.len() == k).count() as u64,
+            binomial(12, k as u64).unwrap()
+        );
+    }
+
+    let digits = [0, 1, 2];
+    let letters = [10, 20];
+    let product: Vec<Vec<&i32>> = cartesian_product(&[&digits[..], &letters[..]]).collect();
+    assert_eq!(product.len(), 6);
+    assert_eq!(product[1], vec![&0, &20]);
+    assert_eq!(product[5], vec![&2, &20]);
+    assert_eq!(cartesian_product(&[&digits[..], &[]]).count(), 0);
+    assert_eq!(cartesian_product::<i32>(&[]).count(), 1);
+    assert_eq!(cartesian_product(&[&digits[..]; 4]).count(), 81);
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Where a sequence of states starts repeating, counted in iterations of the
+/// step function from the initial state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(dead_code)]
+pub struct Cycle {
+    /// The first iteration that's part of the cycle.
+    pub start: u64,
+    /// The number of iterations before the cycle repeats.
+    pub length: u64,
+}
+
+#[allow(dead_code)]
+impl Cycle {
+    /// The earliest iteration that has the same state as `iteration`.
+    pub fn equivalent_iteration(&self, iteration: u64) -> u64 {
+        if iteration < self.start {
+            iteration
+        } else {
+            self.start + (iteration - self.start) % self.length
+        }
+    }
+}
+
+/// Find the cycle with Brent's algorithm, which only keeps two states in
+/// memory at a time.
+///
+/// States are compared by `key`, which must capture everything that affects
+/// the states that come after. This runs forever if the states never repeat.
+#[allow(dead_code)]
+pub fn find_cycle_brent<S, K, F, P>(initial: &S, step: F, key: P) -> Cycle
+where
+    S: Clone,
+    K: PartialEq,
+    F: Fn(&S) -> S,
+    P: Fn(&S) -> K,
+{
+    // Find the cycle length by letting the hare run ahead in windows that
+    // double in size, moving the tortoise up to the hare between windows.
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+    let mut tortoise_key = key(&tortoise);
+    while tortoise_key != key(&hare) {
+        if power == length {
+            tortoise = hare.clone();
+            tortoise_key = key(&tortoise);
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    // Start the hare one cycle length ahead of the tortoise. They meet where
+    // the cycle begins.
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    Cycle { start, length }
+}
+
+/// Find the state after `target` iterations of `step`, skipping ahead as soon
+/// as a repeated state shows that the sequence is cycling.
+///
+/// Every state is kept until a repeat is found, along with its `key` in a hash
+/// map. If only part of the state determines what happens next, `key` can
+/// project out just that part. Anything outside the key in the returned state
+/// comes from the earlier iteration that the target was matched to.
+#[allow(dead_code)]
+pub fn state_at<S, K, F, P>(initial: S, step: F, key: P, target: u64) -> S
+where
+    K: Hash + Eq,
+    F: Fn(&S) -> S,
+    P: Fn(&S) -> K,
+{
+    let mut seen = HashMap::new();
+    let mut states = vec![initial];
+    loop {
+        let iteration = states.len() as u64 - 1;
+        let current = states.last().unwrap();
+        if iteration == target {
+            return states.pop().unwrap();
+        }
+        if let Some(start) = seen.insert(key(current), iteration) {
+            let cycle = Cycle {
+                start,
+                length: iteration - start,
+            };
+            let idx = cycle.equivalent_iteration(target) as usize;
+            return states.swap_remove(idx);
+        }
+        let next = step(current);
+        states.push(next);
+    }
+}
+
+#[test]
+fn test_find_cycle_brent() {
+    // 2 -> 5 -> 26 -> 677 -> ... eventually cycles once it's taken mod 255.
+    let step = |x: &u64| (x * x + 1) % 255;
+    let cycle = find_cycle_brent(&2, step, |x| *x);
+    let mut states = vec![2];
+    for _ in 0..100 {
+        states.push(step(states.last().unwrap()));
+    }
+    let (start, length) = (cycle.start as usize, cycle.length as usize);
+    assert_eq!(states[start], states[start + length]);
+    assert!(start == 0 || states[start - 1] != states[start + length - 1]);
+    assert!((1..length).all(|l| states[start] != states[start + l]));
+
+    // A pure cycle starts at the beginning.
+    assert_eq!(
+        find_cycle_brent(&1, |x| x * 7 % 13, |x| *x),
+        Cycle {
+            start: 0,
+            length: 12
+        }
+    );
+}
+
+#[test]
+fn test_state_at() {
+    let step = |x: &u64| x * 7 % 13;
+    assert_eq!(state_at(1, step, |x| *x, 5), 16_807 % 13);
+    assert_eq!(
+        state_at(1, step, |x| *x, 1_000_000_000_000_000),
+        crate::util::number_theory::mod_pow(7, 1_000_000_000_000_000, 13).unwrap()
+    );
+
+    // The key can be a projection of the state. Here the step count doesn't
+    // affect what comes next, so it's left out of the key.
+    let step = |(count, x): &(u64, u64)| (count + 1, (x * x + 1) % 255);
+    let (_, direct) = (0..1_000).fold((0, 2), |state, _| step(&state));
+    let (count, fast) = state_at((0, 2), step, |(_, x)| *x, 1_000);
+    assert_eq!(fast, direct);
+    assert!(count < 1_000);
+}
+
+#[test]
+fn test_2015_day_25_cycle() {
+    // This is the period that 2015 day 25 used to rely on.
+    let cycle = find_cycle_brent(&20_151_125_u64, |c| c * 252_533 % 33_554_393, |c| *c);
+    assert_eq!(
+        cycle,
+        Cycle {
+            start: 0,
+            length: 16_777_196
+        }
+    );
+}
@@ -0,0 +1,594 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, VecDeque};
+
+/// A directed graph with weighted edges, stored as adjacency lists.
+///
+/// Nodes can be anything that's cheap enough to clone and can be ordered.
+/// Keeping everything ordered means that results (like the order of nodes in
+/// a component) don't change from run to run.
+#[derive(Clone, Debug, Default)]
+#[allow(dead_code)]
+pub struct Graph<N> {
+    edges: BTreeMap<N, Vec<(N, u64)>>,
+}
+
+/// Distances from a single starting node, with enough information to
+/// reconstruct the paths that achieve them.
+#[derive(Clone, Debug)]
+#[allow(dead_code)]
+pub struct ShortestPaths<N> {
+    pub distances: BTreeMap<N, u64>,
+    pub predecessors: BTreeMap<N, N>,
+}
+
+/// The result of pushing as much flow as possible from a source to a sink.
+#[derive(Clone, Debug)]
+#[allow(dead_code)]
+pub struct MaxFlow<N> {
+    pub value: u64,
+    /// Nodes still reachable from the source in the residual graph. This is
+    /// one side of a minimum cut.
+    pub source_side: BTreeSet<N>,
+    /// Edges from the source side to the other side. Their capacities add up
+    /// to `value`.
+    pub min_cut: Vec<(N, N)>,
+}
+
+#[allow(dead_code)]
+impl<N: Clone + Ord> ShortestPaths<N> {
+    pub fn distance(&self, node: &N) -> Option<u64> {
+        self.distances.get(node).copied()
+    }
+
+    /// The nodes along a shortest path from the start to `target`, including
+    /// both ends.
+    pub fn path_to(&self, target: &N) -> Option<Vec<N>> {
+        if !self.distances.contains_key(target) {
+            return None;
+        }
+        Some(reconstruct_path(&self.predecessors, target))
+    }
+}
+
+/// Adjacency lists where nodes are referred to by their position in
+/// [`Graph::nodes`].
+#[allow(dead_code)]
+type IndexedAdjacency = Vec<Vec<(usize, u64)>>;
+
+#[allow(dead_code)]
+fn reconstruct_path<N: Clone + Ord>(predecessors: &BTreeMap<N, N>, target: &N) -> Vec<N> {
+    let mut path = vec![target.clone()];
+    while let Some(previous) = predecessors.get(path.last().unwrap()) {
+        path.push(previous.clone());
+    }
+    path.reverse();
+    path
+}
+
+#[allow(dead_code)]
+impl<N: Clone + Ord> Graph<N> {
+    pub fn new() -> Self {
+        Graph {
+            edges: BTreeMap::new(),
+        }
+    }
+
+    pub fn add_node(&mut self, node: N) {
+        self.edges.entry(node).or_default();
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N, weight: u64) {
+        self.add_node(to.clone());
+        self.edges.entry(from).or_default().push((to, weight));
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.edges.keys()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// The nodes that `node` has edges to, along with the edge weights.
+    pub fn neighbors(&self, node: &N) -> &[(N, u64)] {
+        match self.edges.get(node) {
+            Some(e) => e,
+            None => &[],
+        }
+    }
+
+    /// Shortest paths by number of edges, ignoring weights.
+    pub fn bfs(&self, start: &N) -> ShortestPaths<N> {
+        let mut distances = BTreeMap::from([(start.clone(), 0)]);
+        let mut predecessors = BTreeMap::new();
+        let mut queue = VecDeque::from([start.clone()]);
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[&node];
+            for (neighbor, _) in self.neighbors(&node) {
+                if !distances.contains_key(neighbor) {
+                    distances.insert(neighbor.clone(), distance + 1);
+                    predecessors.insert(neighbor.clone(), node.clone());
+                    queue.push_back(neighbor.clone());
+                }
+            }
+        }
+        ShortestPaths {
+            distances,
+            predecessors,
+        }
+    }
+
+    /// Shortest paths by total edge weight.
+    pub fn dijkstra(&self, start: &N) -> ShortestPaths<N> {
+        let mut distances = BTreeMap::from([(start.clone(), 0)]);
+        let mut predecessors = BTreeMap::new();
+        let mut queue = BinaryHeap::from([Reverse((0, start.clone()))]);
+        while let Some(Reverse((distance, node))) = queue.pop() {
+            if distance > distances[&node] {
+                // We already found a better way here.
+                continue;
+            }
+            for (neighbor, weight) in self.neighbors(&node) {
+                let new_distance = distance + weight;
+                if distances.get(neighbor).is_none_or(|d| new_distance < *d) {
+                    distances.insert(neighbor.clone(), new_distance);
+                    predecessors.insert(neighbor.clone(), node.clone());
+                    queue.push(Reverse((new_distance, neighbor.clone())));
+                }
+            }
+        }
+        ShortestPaths {
+            distances,
+            predecessors,
+        }
+    }
+
+    /// Find a shortest path from `start` to `goal`, guided by `heuristic`.
+    ///
+    /// The heuristic must never overestimate the remaining distance to the
+    /// goal, or the path found might not be the shortest one.
+    pub fn a_star<H>(&self, start: &N, goal: &N, heuristic: H) -> Option<(u64, Vec<N>)>
+    where
+        H: Fn(&N) -> u64,
+    {
+        let mut distances = BTreeMap::from([(start.clone(), 0)]);
+        let mut predecessors = BTreeMap::new();
+        let mut queue = BinaryHeap::from([Reverse((heuristic(start), 0, start.clone()))]);
+        while let Some(Reverse((_, distance, node))) = queue.pop() {
+            if node == *goal {
+                return Some((distance, reconstruct_path(&predecessors, goal)));
+            }
+            if distance > distances[&node] {
+                continue;
+            }
+            for (neighbor, weight) in self.neighbors(&node) {
+                let new_distance = distance + weight;
+                if distances.get(neighbor).is_none_or(|d| new_distance < *d) {
+                    distances.insert(neighbor.clone(), new_distance);
+                    predecessors.insert(neighbor.clone(), node.clone());
+                    let estimate = new_distance + heuristic(neighbor);
+                    queue.push(Reverse((estimate, new_distance, neighbor.clone())));
+                }
+            }
+        }
+        None
+    }
+
+    /// Order the nodes so that every edge points forward.
+    ///
+    /// When several nodes are ready at once, the smallest comes first. If the
+    /// graph has a cycle, the error holds the nodes that couldn't be placed,
+    /// which are those on a cycle or reachable from one.
+    pub fn topological_sort(&self) -> Result<Vec<N>, Vec<N>> {
+        let mut in_degree: BTreeMap<&N, usize> = self.nodes().map(|n| (n, 0)).collect();
+        for edges in self.edges.values() {
+            for (to, _) in edges {
+                *in_degree.get_mut(to).unwrap() += 1;
+            }
+        }
+        let mut ready: BTreeSet<&N> = in_degree
+            .iter()
+            .filter(|(_, d)| **d == 0)
+            .map(|(n, _)| *n)
+            .collect();
+        let mut order = Vec::with_capacity(self.node_count());
+        while let Some(node) = ready.pop_first() {
+            order.push(node.clone());
+            for (to, _) in self.neighbors(node) {
+                let degree = in_degree.get_mut(to).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.insert(to);
+                }
+            }
+        }
+        if order.len() == self.node_count() {
+            Ok(order)
+        } else {
+            Err(in_degree
+                .into_iter()
+                .filter(|(_, d)| *d > 0)
+                .map(|(n, _)| n.clone())
+                .collect())
+        }
+    }
+
+    /// Number the nodes and convert the adjacency lists to use those numbers.
+    fn indexed(&self) -> (Vec<&N>, IndexedAdjacency) {
+        let nodes: Vec<&N> = self.nodes().collect();
+        let index: BTreeMap<&N, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        let adjacency = nodes
+            .iter()
+            .map(|n| {
+                self.neighbors(n)
+                    .iter()
+                    .map(|(to, w)| (index[to], *w))
+                    .collect()
+            })
+            .collect();
+        (nodes, adjacency)
+    }
+
+    /// Find the strongly connected components with Tarjan's algorithm.
+    ///
+    /// Components come out in reverse topological order: no component has an
+    /// edge to one that comes after it. The nodes within each component are
+    /// sorted.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<N>> {
+        let (nodes, adjacency) = self.indexed();
+        let n = nodes.len();
+        let mut index = vec![usize::MAX; n];
+        let mut lowlink = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = Vec::new();
+        let mut next_index = 0;
+        let mut components = Vec::new();
+        for root in 0..n {
+            if index[root] != usize::MAX {
+                continue;
+            }
+            // Recursion is replaced by an explicit stack of (node, next edge)
+            // pairs so that long paths can't overflow the call stack.
+            let mut calls = vec![(root, 0)];
+            index[root] = next_index;
+            lowlink[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            while let Some(&(v, edge)) = calls.last() {
+                if edge < adjacency[v].len() {
+                    calls.last_mut().unwrap().1 += 1;
+                    let w = adjacency[v][edge].0;
+                    if index[w] == usize::MAX {
+                        index[w] = next_index;
+                        lowlink[w] = next_index;
+                        next_index += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        calls.push((w, 0));
+                    } else if on_stack[w] {
+                        lowlink[v] = lowlink[v].min(index[w]);
+                    }
+                    continue;
+                }
+                calls.pop();
+                if let Some(&(parent, _)) = calls.last() {
+                    lowlink[parent] = lowlink[parent].min(lowlink[v]);
+                }
+                if lowlink[v] == index[v] {
+                    let mut component = Vec::new();
+                    loop {
+                        let w = stack.pop().unwrap();
+                        on_stack[w] = false;
+                        component.push(nodes[w].clone());
+                        if w == v {
+                            break;
+                        }
+                    }
+                    component.sort();
+                    components.push(component);
+                }
+            }
+        }
+        components
+    }
+
+    /// Find the groups of nodes that are connected when edge direction is
+    /// ignored. Components are ordered by their smallest node.
+    pub fn connected_components(&self) -> Vec<Vec<N>> {
+        let (nodes, adjacency) = self.indexed();
+        let mut undirected = vec![Vec::new(); nodes.len()];
+        for (from, edges) in adjacency.iter().enumerate() {
+            for (to, _) in edges {
+                undirected[from].push(*to);
+                undirected[*to].push(from);
+            }
+        }
+        let mut seen = vec![false; nodes.len()];
+        let mut components = Vec::new();
+        for root in 0..nodes.len() {
+            if seen[root] {
+                continue;
+            }
+            seen[root] = true;
+            let mut members = vec![root];
+            let mut queue = VecDeque::from([root]);
+            while let Some(v) = queue.pop_front() {
+                for w in undirected[v].iter() {
+                    if !seen[*w] {
+                        seen[*w] = true;
+                        members.push(*w);
+                        queue.push_back(*w);
+                    }
+                }
+            }
+            members.sort_unstable();
+            components.push(members.into_iter().map(|i| nodes[i].clone()).collect());
+        }
+        components
+    }
+
+    /// Find the maximum flow from `source` to `sink` with the Edmonds-Karp
+    /// algorithm, treating edge weights as capacities.
+    pub fn max_flow(&self, source: &N, sink: &N) -> MaxFlow<N> {
+        let (nodes, adjacency) = self.indexed();
+        let find = |node: &N| nodes.binary_search(&node).ok();
+        let (s, t) = match (find(source), find(sink)) {
+            (Some(s), Some(t)) if s != t => (s, t),
+            _ => {
+                return MaxFlow {
+                    value: 0,
+                    source_side: BTreeSet::from([source.clone()]),
+                    min_cut: Vec::new(),
+                }
+            }
+        };
+
+        // Each edge is stored next to its reverse, so `e ^ 1` finds the
+        // partner of edge `e`.
+        let mut targets = Vec::new();
+        let mut residual = Vec::new();
+        let mut outgoing = vec![Vec::new(); nodes.len()];
+        for (from, edges) in adjacency.iter().enumerate() {
+            for (to, capacity) in edges {
+                outgoing[from].push(targets.len());
+                targets.push(*to);
+                residual.push(*capacity);
+                outgoing[*to].push(targets.len());
+                targets.push(from);
+                residual.push(0);
+            }
+        }
+
+        let mut value = 0;
+        loop {
+            // Find the shortest augmenting path.
+            let mut via_edge = vec![usize::MAX; nodes.len()];
+            let mut reached = vec![false; nodes.len()];
+            reached[s] = true;
+            let mut queue = VecDeque::from([s]);
+            while let Some(v) = queue.pop_front() {
+                for e in outgoing[v].iter() {
+                    let w = targets[*e];
+                    if residual[*e] > 0 && !reached[w] {
+                        reached[w] = true;
+                        via_edge[w] = *e;
+                        queue.push_back(w);
+                    }
+                }
+            }
+            if !reached[t] {
+                let source_side = (0..nodes.len())
+                    .filter(|v| reached[*v])
+                    .map(|v| nodes[v].clone())
+                    .collect();
+                let mut min_cut = Vec::new();
+                for (from, edges) in adjacency.iter().enumerate() {
+                    for (to, _) in edges {
+                        if reached[from] && !reached[*to] {
+                            min_cut.push((nodes[from].clone(), nodes[*to].clone()));
+                        }
+                    }
+                }
+                return MaxFlow {
+                    value,
+                    source_side,
+                    min_cut,
+                };
+            }
+
+            // Push as much as the narrowest edge on the path allows.
+            let mut bottleneck = u64::MAX;
+            let mut v = t;
+            while v != s {
+                let e = via_edge[v];
+                bottleneck = bottleneck.min(residual[e]);
+                v = targets[e ^ 1];
+            }
+            let mut v = t;
+            while v != s {
+                let e = via_edge[v];
+                residual[e] -= bottleneck;
+                residual[e ^ 1] += bottleneck;
+                v = targets[e ^ 1];
+            }
+            value += bottleneck;
+        }
+    }
+}
+
+#[cfg(test)]
+fn graph_from_edges(edges: &[(char, char, u64)]) -> Graph<char> {
+    let mut graph = Graph::new();
+    for (from, to, weight) in edges {
+        graph.add_edge(*from, *to, *weight);
+    }
+    graph
+}
+
+#[test]
+fn test_bfs() {
+    let graph = graph_from_edges(&[
+        ('a', 'b', 1),
+        ('b', 'c', 1),
+        ('a', 'd', 1),
+        ('d', 'c', 1),
+        ('c', 'e', 1),
+        ('f', 'a', 1),
+    ]);
+    let paths = graph.bfs(&'a');
+    assert_eq!(paths.distance(&'e'), Some(3));
+    assert_eq!(paths.path_to(&'e'), Some(vec!['a', 'b', 'c', 'e']));
+    assert_eq!(paths.path_to(&'a'), Some(vec!['a']));
+    assert_eq!(paths.distance(&'f'), None);
+    assert_eq!(paths.path_to(&'f'), None);
+}
+
+#[test]
+fn test_dijkstra_and_a_star() {
+    let graph = graph_from_edges(&[
+        ('a', 'b', 7),
+        ('a', 'c', 9),
+        ('a', 'f', 14),
+        ('b', 'c', 10),
+        ('b', 'd', 15),
+        ('c', 'd', 11),
+        ('c', 'f', 2),
+        ('d', 'e', 6),
+        ('f', 'e', 9),
+    ]);
+    let paths = graph.dijkstra(&'a');
+    assert_eq!(paths.distance(&'e'), Some(20));
+    assert_eq!(paths.path_to(&'e'), Some(vec!['a', 'c', 'f', 'e']));
+    assert_eq!(paths.distance(&'d'), Some(20));
+
+    // With no heuristic, A* is the same as Dijkstra.
+    assert_eq!(
+        graph.a_star(&'a', &'e', |_| 0),
+        Some((20, vec!['a', 'c', 'f', 'e']))
+    );
+    // An admissible heuristic still finds the shortest path.
+    let remaining = |n: &char| match n {
+        'a' => 20,
+        'b' | 'c' | 'd' => 6,
+        'f' => 9,
+        _ => 0,
+    };
+    assert_eq!(
+        graph.a_star(&'a', &'e', remaining),
+        Some((20, vec!['a', 'c', 'f', 'e']))
+    );
+    assert_eq!(graph.a_star(&'e', &'a', |_| 0), None);
+}
+
+#[test]
+fn test_a_star_on_grid() {
+    use crate::util::point::Point2;
+
+    // A 5x5 grid with a wall down the middle that has a gap at the bottom.
+    let mut graph = Graph::new();
+    for y in 0..5 {
+        for x in 0..5 {
+            let p = Point2::new(x, y);
+            for q in p.orthogonal_neighbors() {
+                let is_wall = |p: Point2| p.x == 2 && p.y < 4;
+                if (0..5).contains(&q.x) && (0..5).contains(&q.y) && !is_wall(p) && !is_wall(q) {
+                    graph.add_edge(p, q, 1);
+                }
+            }
+        }
+    }
+    let (start, goal) = (Point2::new(0, 0), Point2::new(4, 0));
+    let (distance, path) = graph
+        .a_star(&start, &goal, |p| p.manhattan_distance(goal))
+        .unwrap();
+    assert_eq!(distance, 12);
+    assert_eq!(path.len(), 13);
+    assert!(path.contains(&Point2::new(2, 4)));
+}
+
+#[test]
+fn test_topological_sort() {
+    // The example from 2018 day 7.
+    let graph = graph_from_edges(&[
+        ('C', 'A', 1),
+        ('C', 'F', 1),
+        ('A', 'B', 1),
+        ('A', 'D', 1),
+        ('B', 'E', 1),
+        ('D', 'E', 1),
+        ('F', 'E', 1),
+    ]);
+    assert_eq!(
+        graph.topological_sort(),
+        Ok(vec!['C', 'A', 'B', 'D', 'F', 'E'])
+    );
+
+    let cyclic = graph_from_edges(&[('a', 'b', 1), ('b', 'c', 1), ('c', 'b', 1), ('c', 'd', 1)]);
+    assert_eq!(cyclic.topological_sort(), Err(vec!['b', 'c', 'd']));
+}
+
+#[test]
+fn test_strongly_connected_components() {
+    let graph = graph_from_edges(&[
+        ('a', 'b', 1),
+        ('b', 'c', 1),
+        ('c', 'a', 1),
+        ('b', 'd', 1),
+        ('d', 'e', 1),
+        ('e', 'f', 1),
+        ('f', 'd', 1),
+        ('g', 'f', 1),
+        ('g', 'h', 1),
+        ('h', 'g', 1),
+    ]);
+    assert_eq!(
+        graph.strongly_connected_components(),
+        vec![vec!['d', 'e', 'f'], vec!['a', 'b', 'c'], vec!['g', 'h']]
+    );
+}
+
+#[test]
+fn test_connected_components() {
+    let mut graph = graph_from_edges(&[('a', 'b', 1), ('c', 'b', 1), ('d', 'e', 1)]);
+    graph.add_node('f');
+    assert_eq!(
+        graph.connected_components(),
+        vec![vec!['a', 'b', 'c'], vec!['d', 'e'], vec!['f']]
+    );
+}
+
+#[test]
+fn test_max_flow() {
+    // The example from CLRS, section 26.2.
+    let graph = graph_from_edges(&[
+        ('s', 'a', 16),
+        ('s', 'c', 13),
+        ('a', 'b', 12),
+        ('c', 'a', 4),
+        ('b', 'c', 9),
+        ('c', 'd', 14),
+        ('d', 'b', 7),
+        ('b', 't', 20),
+        ('d', 't', 4),
+    ]);
+    let flow = graph.max_flow(&'s', &'t');
+    assert_eq!(flow.value, 23);
+    assert_eq!(flow.source_side, BTreeSet::from(['s', 'a', 'c', 'd']));
+    assert_eq!(flow.min_cut, vec![('a', 'b'), ('d', 'b'), ('d', 't')]);
+    let cut_capacity: u64 = flow
+        .min_cut
+        .iter()
+        .map(|(from, to)| {
+            graph
+                .neighbors(from)
+                .iter()
+                .filter(|(n, _)| n == to)
+                .map(|(_, c)| c)
+                .sum::<u64>()
+        })
+        .sum();
+    assert_eq!(cut_capacity, flow.value);
+    assert_eq!(graph.max_flow(&'t', &'s').value, 0);
+}
@@ -0,0 +1,391 @@
+use std::fmt::Debug;
+use std::ops::{Add, Sub};
+
+/// Integer types that can bound an [`Interval`].
+pub trait Bound: Copy + Ord + Debug + Add<Output = Self> + Sub<Output = Self> {
+    #[allow(dead_code)]
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! bound_for_integer {
+    ($t: ty) => {
+        impl Bound for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+        }
+    };
+}
+
+bound_for_integer!(i32);
+bound_for_integer!(i64);
+bound_for_integer!(u32);
+bound_for_integer!(u64);
+bound_for_integer!(usize);
+
+/// A contiguous range of integers.
+///
+/// Intervals are stored half-open, so `end` itself isn't included. Use
+/// [`Interval::inclusive`] to build one from an inclusive upper bound, which
+/// is how most puzzle inputs write them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Bound> Interval<T> {
+    /// An interval from `start` up to but not including `end`.
+    pub fn new(start: T, end: T) -> Self {
+        Interval { start, end }
+    }
+
+    /// An interval from `start` up to and including `last`, or `None` if
+    /// `last` is the largest value of its type, since the end would overflow.
+    pub fn inclusive(start: T, last: T) -> Option<Self> {
+        Some(Interval {
+            start,
+            end: last.checked_add(T::ONE)?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> T {
+        if self.is_empty() {
+            T::ZERO
+        } else {
+            self.end - self.start
+        }
+    }
+
+    /// The largest value in the interval, if there is one.
+    #[allow(dead_code)]
+    pub fn last(&self) -> Option<T> {
+        (!self.is_empty()).then(|| self.end - T::ONE)
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, value: T) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    /// Whether every value in `other` is also in this interval.
+    pub fn contains_interval(&self, other: &Interval<T>) -> bool {
+        other.is_empty() || (self.start <= other.start && other.end <= self.end)
+    }
+
+    /// Whether the intervals have at least one value in common.
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    pub fn intersection(&self, other: &Interval<T>) -> Interval<T> {
+        Interval::new(T::max(self.start, other.start), T::min(self.end, other.end))
+    }
+
+    /// The single interval covering both of these, if they overlap or touch.
+    #[allow(dead_code)]
+    pub fn union(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        if self.is_empty() {
+            Some(*other)
+        } else if other.is_empty() {
+            Some(*self)
+        } else if self.start <= other.end && other.start <= self.end {
+            Some(Interval::new(
+                T::min(self.start, other.start),
+                T::max(self.end, other.end),
+            ))
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(&self) -> IntervalIter<T> {
+        IntervalIter {
+            next: self.start,
+            end: self.end,
+        }
+    }
+}
+
+impl<T: Bound> IntoIterator for Interval<T> {
+    type Item = T;
+    type IntoIter = IntervalIter<T>;
+    fn into_iter(self) -> IntervalIter<T> {
+        self.iter()
+    }
+}
+
+/// Iterator over the values in an [`Interval`], in increasing order.
+pub struct IntervalIter<T> {
+    next: T,
+    end: T,
+}
+
+impl<T: Bound> Iterator for IntervalIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        if self.next < self.end {
+            let value = self.next;
+            self.next = self.next + T::ONE;
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+/// A set of integers stored as a sorted list of disjoint intervals.
+///
+/// Intervals that overlap or touch are merged as they're added, so two sets
+/// with the same members always have the same representation.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[allow(dead_code)]
+pub struct RangeSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+#[allow(dead_code)]
+impl<T: Bound> RangeSet<T> {
+    pub fn new() -> Self {
+        RangeSet {
+            intervals: Vec::new(),
+        }
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        if interval.is_empty() {
+            return;
+        }
+        // Find every interval that overlaps or touches the new one, and
+        // replace them all with their union.
+        let first = self.intervals.partition_point(|i| i.end < interval.start);
+        let last = self.intervals.partition_point(|i| i.start <= interval.end);
+        let mut merged = interval;
+        for existing in self.intervals[first..last].iter() {
+            merged = merged.union(existing).unwrap();
+        }
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn remove(&mut self, interval: Interval<T>) {
+        if interval.is_empty() {
+            return;
+        }
+        let first = self.intervals.partition_point(|i| i.end <= interval.start);
+        let last = self.intervals.partition_point(|i| i.start < interval.end);
+        let mut remaining = Vec::new();
+        for existing in self.intervals[first..last].iter() {
+            let before = Interval::new(existing.start, interval.start);
+            let after = Interval::new(interval.end, existing.end);
+            if !before.is_empty() {
+                remaining.push(before);
+            }
+            if !after.is_empty() {
+                remaining.push(after);
+            }
+        }
+        self.intervals.splice(first..last, remaining);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The number of values in the set.
+    pub fn len(&self) -> T {
+        self.intervals
+            .iter()
+            .fold(T::ZERO, |total, interval| total + interval.len())
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let idx = self.intervals.partition_point(|i| i.end <= value);
+        idx < self.intervals.len() && self.intervals[idx].contains(value)
+    }
+
+    /// Whether every value in `interval` is in the set.
+    pub fn contains_interval(&self, interval: &Interval<T>) -> bool {
+        if interval.is_empty() {
+            return true;
+        }
+        let idx = self.intervals.partition_point(|i| i.end <= interval.start);
+        idx < self.intervals.len() && self.intervals[idx].contains_interval(interval)
+    }
+
+    pub fn union(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut result = self.clone();
+        for interval in other.intervals.iter() {
+            result.insert(*interval);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        // Both lists are sorted, so walk them together like a merge.
+        let mut result = RangeSet::new();
+        let (mut a, mut b) = (0, 0);
+        while a < self.intervals.len() && b < other.intervals.len() {
+            let overlap = self.intervals[a].intersection(&other.intervals[b]);
+            if !overlap.is_empty() {
+                result.intervals.push(overlap);
+            }
+            if self.intervals[a].end < other.intervals[b].end {
+                a += 1;
+            } else {
+                b += 1;
+            }
+        }
+        result
+    }
+
+    /// The values in this set that aren't in `other`.
+    pub fn difference(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut result = self.clone();
+        for interval in other.intervals.iter() {
+            result.remove(*interval);
+        }
+        result
+    }
+
+    /// The disjoint intervals that make up the set, in increasing order.
+    pub fn intervals(&self) -> impl Iterator<Item = &Interval<T>> {
+        self.intervals.iter()
+    }
+
+    /// Every value in the set, in increasing order.
+    pub fn values(&self) -> impl Iterator<Item = T> + '_ {
+        self.intervals.iter().flat_map(|i| i.iter())
+    }
+}
+
+impl<T: Bound> FromIterator<Interval<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        for interval in iter {
+            set.insert(interval);
+        }
+        set
+    }
+}
+
+#[test]
+fn test_interval() {
+    let a = Interval::inclusive(2, 8).unwrap();
+    let b = Interval::new(5, 12);
+    assert_eq!(a, Interval::new(2, 9));
+    assert_eq!(a.len(), 7);
+    assert_eq!(a.last(), Some(8));
+    assert!(a.contains(8) && !a.contains(9));
+    assert!(a.overlaps(&b));
+    assert_eq!(a.intersection(&b), Interval::new(5, 9));
+    assert_eq!(a.union(&b), Some(Interval::new(2, 12)));
+    assert!(a.contains_interval(&Interval::inclusive(3, 7).unwrap()));
+    assert!(!a.contains_interval(&b));
+
+    // Touching intervals can be merged, but they don't overlap.
+    let c = Interval::new(9, 10);
+    assert!(!a.overlaps(&c));
+    assert_eq!(a.union(&c), Some(Interval::new(2, 10)));
+    assert_eq!(a.union(&Interval::new(11, 15)), None);
+
+    let empty = Interval::new(4, 4);
+    assert!(empty.is_empty());
+    assert_eq!(empty.len(), 0);
+    assert_eq!(empty.last(), None);
+    assert_eq!(Interval::new(4u64, 1).len(), 0);
+    assert_eq!(
+        Interval::inclusive(3, 6)
+            .unwrap()
+            .iter()
+            .collect::<Vec<i32>>(),
+        vec![3, 4, 5, 6]
+    );
+    assert_eq!(Interval::inclusive(0, u64::MAX), None);
+    assert_eq!(
+        Interval::inclusive(0, u64::MAX - 1),
+        Some(Interval::new(0, u64::MAX))
+    );
+}
+
+#[test]
+fn test_range_set_insert_and_remove() {
+    let mut set: RangeSet<i64> = [
+        Interval::new(10, 15),
+        Interval::new(0, 3),
+        Interval::new(3, 5),
+        Interval::new(20, 25),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        set.intervals().copied().collect::<Vec<_>>(),
+        vec![
+            Interval::new(0, 5),
+            Interval::new(10, 15),
+            Interval::new(20, 25)
+        ]
+    );
+    assert_eq!(set.len(), 15);
+
+    set.insert(Interval::new(4, 21));
+    assert_eq!(
+        set.intervals().copied().collect::<Vec<_>>(),
+        vec![Interval::new(0, 25)]
+    );
+
+    set.remove(Interval::new(5, 10));
+    set.remove(Interval::inclusive(24, 30).unwrap());
+    assert_eq!(
+        set.intervals().copied().collect::<Vec<_>>(),
+        vec![Interval::new(0, 5), Interval::new(10, 24)]
+    );
+    assert!(set.contains(4) && !set.contains(5) && set.contains(23));
+    assert!(set.contains_interval(&Interval::new(12, 20)));
+    assert!(!set.contains_interval(&Interval::new(3, 12)));
+    assert_eq!(
+        set.values().take(7).collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 4, 10, 11]
+    );
+}
+
+#[test]
+fn test_range_set_operations() {
+    let a: RangeSet<u64> = [Interval::new(0, 10), Interval::new(20, 30)]
+        .into_iter()
+        .collect();
+    let b: RangeSet<u64> = [Interval::new(5, 25), Interval::new(28, 40)]
+        .into_iter()
+        .collect();
+    assert_eq!(
+        a.union(&b).intervals().copied().collect::<Vec<_>>(),
+        vec![Interval::new(0, 40)]
+    );
+    assert_eq!(
+        a.intersection(&b).intervals().copied().collect::<Vec<_>>(),
+        vec![
+            Interval::new(5, 10),
+            Interval::new(20, 25),
+            Interval::new(28, 30)
+        ]
+    );
+    assert_eq!(
+        a.difference(&b).intervals().copied().collect::<Vec<_>>(),
+        vec![Interval::new(0, 5), Interval::new(25, 28)]
+    );
+    assert_eq!(
+        b.difference(&a).intervals().copied().collect::<Vec<_>>(),
+        vec![Interval::new(10, 20), Interval::new(30, 40)]
+    );
+    assert!(a.difference(&a).is_empty());
+}
@@ -0,0 +1,333 @@
+use std::fmt::Display;
+
+/// A JSON value.
+///
+/// Numbers keep their original text so that integers of any size survive a
+/// round trip.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Look up a key in an object. Returns `None` for anything else.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+fn write_string(f: &mut std::fmt::Formatter<'_>, s: &str) -> std::fmt::Result {
+    write!(f, "\"")?;
+    for chr in s.chars() {
+        match chr {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(s) => write_string(f, s),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(members) => {
+                write!(f, "{{")?;
+                for (idx, (key, value)) in members.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let chr = self.peek()?;
+        self.position += chr.len_utf8();
+        Some(chr)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!(
+            "Failed to parse JSON at byte {}: {}",
+            self.position, message
+        )
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(self.error(&format!("expected '{}'", expected))),
+        }
+    }
+
+    fn expect_word(&mut self, word: &str, value: Json) -> Result<Json, String> {
+        if self.input[self.position..].starts_with(word) {
+            self.position += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("unexpected character"))
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        let value = match self.peek() {
+            Some('n') => self.expect_word("null", Json::Null)?,
+            Some('t') => self.expect_word("true", Json::Bool(true))?,
+            Some('f') => self.expect_word("false", Json::Bool(false))?,
+            Some('"') => Json::String(self.string()?),
+            Some('[') => self.array()?,
+            Some('{') => self.object()?,
+            Some('-' | '0'..='9') => self.number()?,
+            Some(_) => return Err(self.error("unexpected character")),
+            None => return Err(self.error("unexpected end of input")),
+        };
+        self.skip_whitespace();
+        Ok(value)
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.position;
+        while let Some('0'..='9') = self.peek() {
+            self.position += 1;
+        }
+        self.position - start
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.position;
+        if self.peek() == Some('-') {
+            self.position += 1;
+        }
+        let leading_zero = self.peek() == Some('0');
+        match self.digits() {
+            0 => return Err(self.error("expected a digit")),
+            n if n > 1 && leading_zero => return Err(self.error("leading zero")),
+            _ => (),
+        }
+        if self.peek() == Some('.') {
+            self.position += 1;
+            if self.digits() == 0 {
+                return Err(self.error("expected a digit"));
+            }
+        }
+        if let Some('e' | 'E') = self.peek() {
+            self.position += 1;
+            if let Some('+' | '-') = self.peek() {
+                self.position += 1;
+            }
+            if self.digits() == 0 {
+                return Err(self.error("expected a digit"));
+            }
+        }
+        Ok(Json::Number(self.input[start..self.position].to_string()))
+    }
+
+    fn hex_escape(&mut self) -> Result<u32, String> {
+        let digits = self
+            .input
+            .get(self.position..self.position + 4)
+            .ok_or_else(|| self.error("unexpected end of input"))?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(self.error(&format!("bad unicode escape '{}'", digits)));
+        }
+        let code = u32::from_str_radix(digits, 16).unwrap();
+        self.position += 4;
+        Ok(code)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => {
+                    let chr = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let mut code = self.hex_escape()?;
+                            // Characters outside the basic plane are written as
+                            // a pair of surrogates.
+                            if (0xd800..0xdc00).contains(&code)
+                                && self.input[self.position..].starts_with("\\u")
+                            {
+                                self.position += 2;
+                                let low = self.hex_escape()?;
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return Err(self.error("bad unicode escape"));
+                                }
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                            }
+                            char::from_u32(code).ok_or_else(|| self.error("bad unicode escape"))?
+                        }
+                        _ => return Err(self.error("bad escape sequence")),
+                    };
+                    s.push(chr);
+                }
+                Some(c) if c.is_control() => return Err(self.error("control character in string")),
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        self.skip_whitespace();
+        let mut values = Vec::new();
+        if self.peek() == Some(']') {
+            self.position += 1;
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            match self.next() {
+                Some(',') => (),
+                Some(']') => return Ok(Json::Array(values)),
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        self.skip_whitespace();
+        let mut members = Vec::new();
+        if self.peek() == Some('}') {
+            self.position += 1;
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            members.push((key, self.value()?));
+            match self.next() {
+                Some(',') => (),
+                Some('}') => return Ok(Json::Object(members)),
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+}
+
+pub fn parse_json(input: &str) -> Result<Json, String> {
+    let mut parser = Parser { input, position: 0 };
+    let value = parser.value()?;
+    if parser.position != input.len() {
+        return Err(parser.error("unexpected trailing characters"));
+    }
+    Ok(value)
+}
+
+#[test]
+fn test_parse_json() {
+    assert_eq!(
+        parse_json(" {\"a\": [1, -2.5e3, true, null], \"b\" : \"x\\ny\"}\n"),
+        Ok(Json::Object(vec![
+            (
+                "a".to_string(),
+                Json::Array(vec![
+                    Json::Number("1".to_string()),
+                    Json::Number("-2.5e3".to_string()),
+                    Json::Bool(true),
+                    Json::Null
+                ])
+            ),
+            ("b".to_string(), Json::String("x\ny".to_string()))
+        ]))
+    );
+    assert_eq!(
+        parse_json("\"\\u00e9\\ud83c\\udf84\""),
+        Ok(Json::String("é🎄".to_string()))
+    );
+    assert_eq!(parse_json("[]"), Ok(Json::Array(vec![])));
+    for bad in ["", "[1,]", "{\"a\" 1}", "01", "\"abc", "[1] 2", "tru", "1."] {
+        assert!(parse_json(bad).is_err(), "{:?} should fail", bad);
+    }
+}
+
+#[test]
+fn test_json_round_trip() {
+    let value = Json::Object(vec![
+        (
+            "value".to_string(),
+            Json::Number("123456789012345678901234567890".to_string()),
+        ),
+        (
+            "text".to_string(),
+            Json::String("quote \" backslash \\ tab \t bell \u{7}".to_string()),
+        ),
+        ("list".to_string(), Json::Array(vec![Json::Bool(false)])),
+    ]);
+    assert_eq!(parse_json(&value.to_string()), Ok(value.clone()));
+    assert_eq!(
+        value.get("value"),
+        Some(&Json::Number("123456789012345678901234567890".to_string()))
+    );
+    assert_eq!(value.get("missing"), None);
+}
@@ -0,0 +1,243 @@
+use std::ops::Index;
+
+use crate::util::bigint::BigUint;
+use crate::util::number_theory::mod_mul;
+
+/// The arithmetic a [`Matrix`] needs for its elements.
+///
+/// This is implemented by a separate value rather than by the element type so
+/// that rings like "integers mod p" can carry their modulus around.
+pub trait Ring {
+    type Element: Clone;
+    fn zero(&self) -> Self::Element;
+    fn one(&self) -> Self::Element;
+    fn add(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
+    fn mul(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
+}
+
+/// Ordinary `u64` arithmetic. A result that doesn't fit is `None`, and so is
+/// anything computed from it. Use [`BigNatural`] when the answer is expected
+/// to be that large.
+pub struct Natural;
+
+impl Ring for Natural {
+    type Element = Option<u64>;
+    fn zero(&self) -> Option<u64> {
+        Some(0)
+    }
+    fn one(&self) -> Option<u64> {
+        Some(1)
+    }
+    fn add(&self, a: &Option<u64>, b: &Option<u64>) -> Option<u64> {
+        (*a)?.checked_add((*b)?)
+    }
+    fn mul(&self, a: &Option<u64>, b: &Option<u64>) -> Option<u64> {
+        (*a)?.checked_mul((*b)?)
+    }
+}
+
+/// Exact arithmetic on arbitrarily large natural numbers.
+pub struct BigNatural;
+
+impl Ring for BigNatural {
+    type Element = BigUint;
+    fn zero(&self) -> BigUint {
+        BigUint::zero()
+    }
+    fn one(&self) -> BigUint {
+        BigUint::one()
+    }
+    fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a + b
+    }
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a * b
+    }
+}
+
+/// Arithmetic on `u64` values modulo some number.
+pub struct Modular(u64);
+
+impl Modular {
+    /// Arithmetic modulo `modulus`, or `None` if it's zero.
+    pub fn new(modulus: u64) -> Option<Self> {
+        (modulus != 0).then_some(Modular(modulus))
+    }
+}
+
+impl Ring for Modular {
+    type Element = u64;
+    fn zero(&self) -> u64 {
+        0
+    }
+    fn one(&self) -> u64 {
+        1 % self.0
+    }
+    fn add(&self, a: &u64, b: &u64) -> u64 {
+        ((*a as u128 + *b as u128) % self.0 as u128) as u64
+    }
+    fn mul(&self, a: &u64, b: &u64) -> u64 {
+        mod_mul(*a, *b, self.0)
+    }
+}
+
+/// A dense matrix, stored in row-major order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T: Clone> Matrix<T> {
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, String> {
+        let cols = rows.first().map_or(0, |r| r.len());
+        if let Some(r) = rows.iter().find(|r| r.len() != cols) {
+            return Err(format!(
+                "Matrix rows must all have {} columns (found one with {})",
+                cols,
+                r.len()
+            ));
+        }
+        Ok(Matrix {
+            rows: rows.len(),
+            cols,
+            data: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn identity<R: Ring<Element = T>>(size: usize, ring: &R) -> Self {
+        let mut data = vec![ring.zero(); size * size];
+        for i in 0..size {
+            data[i * size + i] = ring.one();
+        }
+        Matrix {
+            rows: size,
+            cols: size,
+            data,
+        }
+    }
+
+    pub fn mul<R: Ring<Element = T>>(&self, other: &Matrix<T>, ring: &R) -> Matrix<T> {
+        assert_eq!(self.cols, other.rows, "matrix dimensions don't match");
+        let mut data = Vec::with_capacity(self.rows * other.cols);
+        for row in 0..self.rows {
+            for col in 0..other.cols {
+                let mut sum = ring.zero();
+                for k in 0..self.cols {
+                    sum = ring.add(&sum, &ring.mul(&self[(row, k)], &other[(k, col)]));
+                }
+                data.push(sum);
+            }
+        }
+        Matrix {
+            rows: self.rows,
+            cols: other.cols,
+            data,
+        }
+    }
+
+    pub fn mul_vector<R: Ring<Element = T>>(&self, vector: &[T], ring: &R) -> Vec<T> {
+        assert_eq!(self.cols, vector.len(), "matrix dimensions don't match");
+        (0..self.rows)
+            .map(|row| {
+                (0..self.cols).fold(ring.zero(), |sum, k| {
+                    ring.add(&sum, &ring.mul(&self[(row, k)], &vector[k]))
+                })
+            })
+            .collect()
+    }
+
+    /// Raise a square matrix to a power by repeated squaring, which takes
+    /// O(log exponent) multiplications.
+    pub fn pow<R: Ring<Element = T>>(&self, exponent: u64, ring: &R) -> Matrix<T> {
+        assert_eq!(self.rows, self.cols, "only square matrices have powers");
+        let mut result = Matrix::identity(self.rows, ring);
+        let mut base = self.clone();
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base, ring);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.mul(&base, ring);
+            }
+        }
+        result
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(row < self.rows && col < self.cols);
+        &self.data[row * self.cols + col]
+    }
+}
+
+/// A matrix of [`Natural`] elements.
+#[cfg(test)]
+fn naturals(rows: Vec<Vec<u64>>) -> Matrix<Option<u64>> {
+    Matrix::from_rows(
+        rows.into_iter()
+            .map(|r| r.into_iter().map(Some).collect())
+            .collect(),
+    )
+    .unwrap()
+}
+
+#[cfg(test)]
+fn fibonacci_matrix() -> Matrix<u64> {
+    Matrix::from_rows(vec![vec![1, 1], vec![1, 0]]).unwrap()
+}
+
+#[test]
+fn test_matrix_mul() {
+    let a = naturals(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+    let b = naturals(vec![vec![7, 8], vec![9, 10], vec![11, 12]]);
+    assert_eq!(
+        a.mul(&b, &Natural),
+        naturals(vec![vec![58, 64], vec![139, 154]])
+    );
+    assert_eq!(
+        a.mul_vector(&[Some(1), Some(0), Some(2)], &Natural),
+        vec![Some(7), Some(16)]
+    );
+    assert_eq!(a.mul(&Matrix::identity(3, &Natural), &Natural), a);
+    assert!(Matrix::from_rows(vec![vec![1, 2], vec![3]]).is_err());
+}
+
+#[test]
+fn test_matrix_pow() {
+    let fibonacci = naturals(vec![vec![1, 1], vec![1, 0]]);
+    assert_eq!(fibonacci.pow(0, &Natural), Matrix::identity(2, &Natural));
+    assert_eq!(fibonacci.pow(10, &Natural)[(0, 1)], Some(55));
+    assert_eq!(
+        fibonacci.pow(90, &Natural)[(0, 1)],
+        Some(2_880_067_194_370_816_120)
+    );
+    // F(94) is the first Fibonacci number too big for a u64.
+    assert_eq!(fibonacci.pow(94, &Natural)[(0, 1)], None);
+    // The last digits of the Fibonacci numbers repeat every 60 terms, and
+    // 10^15 is 40 more than a multiple of 60.
+    let f_40 = 102_334_155;
+    assert_eq!(
+        fibonacci_matrix().pow(1_000_000_000_000_000, &Modular::new(10).unwrap())[(0, 1)],
+        f_40 % 10
+    );
+    assert_eq!(
+        fibonacci_matrix().pow(90, &Modular::new(1_000_000_007).unwrap())[(0, 1)],
+        2_880_067_194_370_816_120 % 1_000_000_007
+    );
+    assert!(Modular::new(0).is_none());
+    let big_fibonacci = Matrix::from_rows(vec![
+        vec![BigUint::one(), BigUint::one()],
+        vec![BigUint::one(), BigUint::zero()],
+    ])
+    .unwrap();
+    assert_eq!(
+        big_fibonacci.pow(100, &BigNatural)[(0, 1)].to_string(),
+        "354224848179261915075"
+    );
+}
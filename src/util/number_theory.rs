@@ -0,0 +1,225 @@
+//! Modular arithmetic on `u64` values. Intermediate results are computed in
+//! 128 bits, so any modulus that fits in a `u64` is safe from overflow. A
+//! modulus of 0 has no remainders, so the functions that take one return
+//! `None` for it.
+
+use std::collections::HashMap;
+
+pub fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// The least common multiple, or `None` if it doesn't fit in a `u64`.
+#[allow(dead_code)]
+pub fn lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Find `(g, x, y)` such that `g = gcd(a, b)` and `a * x + b * y = g`.
+///
+/// Returns `None` when `g` is 2^63, which is too big for an `i64`. That only
+/// happens when both inputs are 0 or `i64::MIN`, and at least one of them is
+/// `i64::MIN`.
+#[allow(dead_code)]
+pub fn extended_gcd(a: i64, b: i64) -> Option<(i64, i64, i64)> {
+    let (g, x, y) = extended_gcd_i128(a as i128, b as i128);
+    Some((
+        i64::try_from(g).ok()?,
+        i64::try_from(x).ok()?,
+        i64::try_from(y).ok()?,
+    ))
+}
+
+fn extended_gcd_i128(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1_i128, 0_i128);
+    let (mut old_y, mut y) = (0_i128, 1_i128);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+    if old_r < 0 {
+        (old_r, old_x, old_y) = (-old_r, -old_x, -old_y);
+    }
+    (old_r, old_x, old_y)
+}
+
+/// The modulus must not be 0.
+pub fn mod_mul(a: u64, b: u64, modulus: u64) -> u64 {
+    ((a as u128 * b as u128) % modulus as u128) as u64
+}
+
+/// Compute `base.pow(exponent) % modulus` by repeated squaring.
+pub fn mod_pow(base: u64, exponent: u64, modulus: u64) -> Option<u64> {
+    if modulus == 0 {
+        return None;
+    }
+    let mut result = 1 % modulus;
+    let mut base = base % modulus;
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mod_mul(result, base, modulus);
+        }
+        base = mod_mul(base, base, modulus);
+        exponent >>= 1;
+    }
+    Some(result)
+}
+
+/// Find `x` such that `a * x % modulus == 1`, if it exists.
+pub fn mod_inv(a: u64, modulus: u64) -> Option<u64> {
+    if modulus == 0 {
+        return None;
+    }
+    let (g, x, _) = extended_gcd_i128((a % modulus) as i128, modulus as i128);
+    if g != 1 {
+        return None;
+    }
+    Some(x.rem_euclid(modulus as i128) as u64)
+}
+
+/// Solve a system of congruences `x % modulus == residue` with the Chinese
+/// remainder theorem.
+///
+/// The moduli don't need to be pairwise coprime. The result is the smallest
+/// solution along with the combined modulus (the lcm of all of them), or
+/// `None` if the congruences contradict each other or the combined modulus
+/// doesn't fit in a `u64`.
+#[allow(dead_code)]
+pub fn crt(congruences: &[(u64, u64)]) -> Option<(u64, u64)> {
+    let (mut residue, mut modulus) = (0_i128, 1_i128);
+    for (r, m) in congruences {
+        if *m == 0 {
+            return None;
+        }
+        let (r, m) = (*r as i128 % *m as i128, *m as i128);
+        let (g, p, _) = extended_gcd_i128(modulus, m);
+        let difference = r - residue;
+        if difference % g != 0 {
+            return None;
+        }
+        // Find how many steps of the old modulus it takes to also satisfy the
+        // new congruence.
+        let step = m / g;
+        let k = ((difference / g).rem_euclid(step) as u128 * p.rem_euclid(step) as u128
+            % step as u128) as i128;
+        let combined = modulus.checked_mul(step)?;
+        if combined > u64::MAX as i128 {
+            return None;
+        }
+        residue = (residue + modulus * k).rem_euclid(combined);
+        modulus = combined;
+    }
+    Some((residue as u64, modulus as u64))
+}
+
+/// Find the smallest `x` such that `mod_pow(base, x, modulus) == target`
+/// with the baby-step giant-step algorithm. This takes time and memory
+/// proportional to the square root of the modulus.
+///
+/// `base` must be coprime to `modulus`, which is always true when the modulus
+/// is prime and doesn't divide the base.
+pub fn discrete_log(base: u64, target: u64, modulus: u64) -> Option<u64> {
+    if modulus == 0 {
+        return None;
+    }
+    let target = target % modulus;
+    let steps = (modulus as f64).sqrt().ceil() as u64;
+    // Baby steps: remember the first exponent that reaches each value.
+    let mut baby_steps = HashMap::with_capacity(steps as usize);
+    let mut value = 1 % modulus;
+    for j in 0..steps {
+        baby_steps.entry(value).or_insert(j);
+        value = mod_mul(value, base, modulus);
+    }
+    // Giant steps: divide the target by base^steps until we land on a value
+    // we've seen.
+    let giant_step = mod_inv(mod_pow(base, steps, modulus)?, modulus)?;
+    let mut gamma = target;
+    for i in 0..steps {
+        if let Some(j) = baby_steps.get(&gamma) {
+            return Some(i * steps + j);
+        }
+        gamma = mod_mul(gamma, giant_step, modulus);
+    }
+    None
+}
+
+#[test]
+fn test_gcd_and_lcm() {
+    assert_eq!(gcd(48, 18), 6);
+    assert_eq!(gcd(0, 7), 7);
+    assert_eq!(lcm(4, 6), Some(12));
+    assert_eq!(lcm(u64::MAX, 2), None);
+    for (a, b) in [(240, 46), (-240, 46), (17, -5), (0, 9)] {
+        let (g, x, y) = extended_gcd(a, b).unwrap();
+        assert_eq!(g, gcd(a.unsigned_abs(), b.unsigned_abs()) as i64);
+        assert_eq!(a * x + b * y, g);
+    }
+    assert_eq!(extended_gcd(i64::MIN, 0), None);
+    assert_eq!(extended_gcd(i64::MIN, i64::MIN), None);
+    let (g, x, y) = extended_gcd(i64::MIN, 3).unwrap();
+    assert_eq!((g, i64::MIN as i128 * x as i128 + 3 * y as i128), (1, 1));
+}
+
+#[test]
+fn test_mod_pow_and_inverse() {
+    assert_eq!(mod_pow(2, 10, 1_000), Some(24));
+    assert_eq!(mod_pow(5, 0, 7), Some(1));
+    assert_eq!(mod_pow(5, 0, 1), Some(0));
+    assert_eq!(mod_pow(5, 3, 0), None);
+    // Large enough that the products overflow without u128.
+    let p = 18_446_744_073_709_551_557;
+    assert_eq!(mod_pow(3, p - 1, p), Some(1));
+    assert_eq!(mod_inv(3, 11), Some(4));
+    assert_eq!(mod_inv(6, 9), None);
+    assert_eq!(mod_inv(6, 0), None);
+    let inverse = mod_inv(123_456_789, p).unwrap();
+    assert_eq!(mod_mul(inverse, 123_456_789, p), 1);
+}
+
+#[test]
+fn test_crt() {
+    assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+    // The moduli don't need to be coprime.
+    assert_eq!(crt(&[(3, 4), (5, 6)]), Some((11, 12)));
+    assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+    assert_eq!(crt(&[]), Some((0, 1)));
+    assert_eq!(crt(&[(2, 3), (0, 0)]), None);
+    // 2020 day 13
+    assert_eq!(
+        crt(&[
+            (0, 7),
+            (13 - 1, 13),
+            (59 - 4, 59),
+            (31 - 6, 31),
+            (19 - 7, 19)
+        ]),
+        Some((1_068_781, 3_162_341))
+    );
+}
+
+#[test]
+fn test_discrete_log() {
+    assert_eq!(discrete_log(7, 5_764_801, 20_201_227), Some(8));
+    assert_eq!(discrete_log(7, 17_807_724, 20_201_227), Some(11));
+    assert_eq!(discrete_log(3, 1, 17), Some(0));
+    assert_eq!(discrete_log(2, 3, 7), None);
+    assert_eq!(discrete_log(2, 3, 0), None);
+    for x in [0, 1, 1_000, 1_001, 99_999] {
+        let target = mod_pow(5, x, 1_000_003).unwrap();
+        let found = discrete_log(5, target, 1_000_003).unwrap();
+        assert!(found <= x);
+        assert_eq!(mod_pow(5, found, 1_000_003), Some(target));
+    }
+}
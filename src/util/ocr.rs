@@ -0,0 +1,212 @@
+//! Read the block letters that some puzzles draw as their answer.
+//!
+//! Advent of Code uses two fonts: a small one whose letters are six pixels
+//! tall and (usually) four wide, and a large one whose letters are ten pixels
+//! tall and six wide. Neither font uses every letter, so these tables only
+//! hold the ones that have turned up in puzzles.
+
+const SMALL_HEIGHT: usize = 6;
+const LARGE_HEIGHT: usize = 10;
+
+const SMALL_GLYPHS: &[(char, [&str; SMALL_HEIGHT])] = &[
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', ["###", ".#.", ".#.", ".#.", ".#.", "###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[rustfmt::skip]
+const LARGE_GLYPHS: &[(char, [&str; LARGE_HEIGHT])] = &[
+    ('A', ["..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#"]),
+    ('B', ["#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#", "#....#", "#####."]),
+    ('C', [".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#....#", ".####."]),
+    ('E', ["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('F', ["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('G', [".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#", "#...##", ".###.#"]),
+    ('H', ["#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#", "#....#"]),
+    ('J', ["...###", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.", "#...#.", "#...#.", ".###.."]),
+    ('K', ["#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..", "#...#.", "#....#"]),
+    ('L', ["#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('N', ["#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##", "#...##", "#....#"]),
+    ('P', ["#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('R', ["#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.", "#....#", "#....#"]),
+    ('X', ["#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.", "#....#", "#....#"]),
+    ('Z', ["######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....", "#.....", "######"]),
+];
+
+/// Compare the pixels in `columns` of `grid` against a glyph.
+fn matches_glyph(grid: &[Vec<bool>], columns: &[usize], glyph: &[&str]) -> bool {
+    glyph.iter().zip(grid).all(|(glyph_row, grid_row)| {
+        glyph_row.len() == columns.len()
+            && glyph_row
+                .bytes()
+                .zip(columns)
+                .all(|(pixel, col)| (pixel == b'#') == grid_row.get(*col).copied().unwrap_or(false))
+    })
+}
+
+/// Read the letters drawn in a grid of pixels, where `true` is lit.
+///
+/// Letters are separated by columns with no lit pixels, and blank rows above
+/// and below the text are ignored. Rows don't all need to be the same length.
+///
+/// The small `Y` is five pixels wide, so with the usual five column spacing it
+/// touches the letter after it. When a run of lit columns isn't a letter on its
+/// own, the widest letter that it starts with is taken off the front.
+pub fn ocr(grid: &[Vec<bool>]) -> Result<String, String> {
+    let lit_rows: Vec<usize> = (0..grid.len())
+        .filter(|r| grid[*r].iter().any(|p| *p))
+        .collect();
+    let (first, last) = match (lit_rows.first(), lit_rows.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return Ok(String::new()),
+    };
+    let grid = &grid[first..=last];
+    let glyphs = match grid.len() {
+        SMALL_HEIGHT => SMALL_GLYPHS
+            .iter()
+            .map(|(c, g)| (*c, &g[..]))
+            .collect::<Vec<_>>(),
+        LARGE_HEIGHT => LARGE_GLYPHS.iter().map(|(c, g)| (*c, &g[..])).collect(),
+        h => {
+            return Err(format!(
+                "Letters must be {} or {} pixels tall (found {})",
+                SMALL_HEIGHT, LARGE_HEIGHT, h
+            ))
+        }
+    };
+
+    let width = grid.iter().map(|r| r.len()).max().unwrap_or(0);
+    let is_lit = |col: usize| grid.iter().any(|r| r.get(col).copied().unwrap_or(false));
+    let mut text = String::new();
+    let mut col = 0;
+    while col < width {
+        if !is_lit(col) {
+            col += 1;
+            continue;
+        }
+        let columns: Vec<usize> = (col..width).take_while(|c| is_lit(*c)).collect();
+        let whole = glyphs
+            .iter()
+            .find(|(_, glyph)| matches_glyph(grid, &columns, glyph));
+        let prefix = || {
+            glyphs
+                .iter()
+                .filter(|(_, glyph)| glyph[0].len() < columns.len())
+                .filter(|(_, glyph)| matches_glyph(grid, &columns[..glyph[0].len()], glyph))
+                .max_by_key(|(_, glyph)| glyph[0].len())
+        };
+        match whole.or_else(prefix) {
+            Some((letter, glyph)) => {
+                text.push(*letter);
+                col += glyph[0].len();
+            }
+            None => return Err(format!("Unknown glyph at column {}", col)),
+        }
+    }
+    Ok(text)
+}
+
+/// Read the letters in a picture drawn with `#` for lit pixels. Any other
+/// character (usually `.` or a space) is dark.
+pub fn ocr_str(picture: &str) -> Result<String, String> {
+    let grid: Vec<Vec<bool>> = picture
+        .lines()
+        .map(|line| line.chars().map(|c| c == '#').collect())
+        .collect();
+    ocr(&grid)
+}
+
+/// Draw some text in one of the fonts, with a blank column between letters.
+#[cfg(test)]
+fn render(text: &str, large: bool) -> String {
+    let height = if large { LARGE_HEIGHT } else { SMALL_HEIGHT };
+    let glyph = |letter: char| -> Vec<&str> {
+        if large {
+            LARGE_GLYPHS
+                .iter()
+                .find(|(c, _)| *c == letter)
+                .unwrap()
+                .1
+                .to_vec()
+        } else {
+            SMALL_GLYPHS
+                .iter()
+                .find(|(c, _)| *c == letter)
+                .unwrap()
+                .1
+                .to_vec()
+        }
+    };
+    (0..height)
+        .map(|row| {
+            text.chars()
+                .map(|letter| glyph(letter)[row])
+                .collect::<Vec<_>>()
+                .join(".")
+                + "\n"
+        })
+        .collect()
+}
+
+#[test]
+fn test_ocr_small_font() {
+    let alphabet: String = SMALL_GLYPHS.iter().map(|(c, _)| *c).collect();
+    assert_eq!(ocr_str(&render(&alphabet, false)), Ok(alphabet));
+    // 2016 day 8 draws its answer with spaces for dark pixels, and with extra
+    // space around the letters.
+    let picture = "\n\
+        ####  ##  ###  \n\
+        #    #  # #  # \n\
+        ###  #  # #  # \n\
+        #    #  # ###  \n\
+        #    #  # # #  \n\
+        ####  ##  #  # \n\
+        \n";
+    assert_eq!(ocr_str(picture), Ok("EOR".to_string()));
+    assert_eq!(ocr_str("...\n..."), Ok(String::new()));
+}
+
+#[test]
+fn test_ocr_touching_letters() {
+    // Every letter gets five columns, so the "Y" runs into the "E".
+    let picture = ".##..#...#####.\n\
+        #..#.#...##....\n\
+        #..#..#.#.###..\n\
+        ####...#..#....\n\
+        #..#...#..#....\n\
+        #..#...#..####.\n";
+    assert_eq!(ocr_str(picture), Ok("AYE".to_string()));
+}
+
+#[test]
+fn test_ocr_large_font() {
+    let alphabet: String = LARGE_GLYPHS.iter().map(|(c, _)| *c).collect();
+    assert_eq!(ocr_str(&render(&alphabet, true)), Ok(alphabet));
+}
+
+#[test]
+fn test_ocr_errors() {
+    let mut picture = render("HI", false);
+    // Knock a pixel out of the "I", which starts at column 5.
+    picture.replace_range(5..6, ".");
+    assert_eq!(
+        ocr_str(&picture),
+        Err("Unknown glyph at column 5".to_string())
+    );
+    assert!(ocr_str("#\n#\n#").is_err());
+}
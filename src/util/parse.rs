@@ -0,0 +1,241 @@
+use std::io::BufRead;
+
+use crate::util::read_lines;
+
+/// Split the input into groups of lines separated by blank lines.
+///
+/// Lines may end with `\n` or `\r\n`, and the last line doesn't need a line
+/// ending at all. Runs of several blank lines (or blank lines at the start or
+/// end) never produce empty sections. A line that's only whitespace counts as
+/// blank.
+pub fn sections(input: &str) -> Vec<Vec<&str>> {
+    let mut sections = Vec::new();
+    let mut current = Vec::new();
+    for line in input.lines() {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                sections.push(current);
+                current = Vec::new();
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        sections.push(current);
+    }
+    sections
+}
+
+/// Like [`sections`], but reading one section at a time so that the whole
+/// input never has to be in memory.
+pub fn read_sections(
+    input: &mut dyn BufRead,
+) -> impl Iterator<Item = Result<Vec<String>, String>> + '_ {
+    let mut lines = read_lines(input);
+    std::iter::from_fn(move || {
+        let mut section = Vec::new();
+        for line in lines.by_ref() {
+            match line {
+                Ok(l) if l.trim().is_empty() => {
+                    if !section.is_empty() {
+                        return Some(Ok(section));
+                    }
+                }
+                Ok(l) => section.push(l),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        (!section.is_empty()).then_some(Ok(section))
+    })
+}
+
+/// Items drawn as `[X]` cells stacked in columns, with a row of labels along
+/// the bottom:
+///
+/// ```text
+///     [D]
+/// [N] [C]
+/// [Z] [M] [P]
+///  1   2   3
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnDiagram {
+    pub labels: Vec<String>,
+    /// One stack per label, listed from the bottom up.
+    pub stacks: Vec<Vec<char>>,
+}
+
+impl ColumnDiagram {
+    /// The position of the stack with the given label.
+    pub fn index_of(&self, label: &str) -> Option<usize> {
+        self.labels.iter().position(|l| l == label)
+    }
+}
+
+/// Parse a [`ColumnDiagram`], whose last line is the label row.
+///
+/// Every item has to sit directly above a label, and there can't be gaps under
+/// an item. Labels must be unique. If they're numbers, they must count up from
+/// 1, which is how puzzles usually number them.
+pub fn parse_column_diagram(lines: &[&str]) -> Result<ColumnDiagram, String> {
+    let (label_row, item_rows) = match lines.split_last() {
+        Some(split) => split,
+        None => return Err("Failed to find a column diagram".to_string()),
+    };
+
+    // Each label covers the character columns it's written in.
+    let label_chars: Vec<char> = label_row.chars().chain([' ']).collect();
+    let mut labels: Vec<(usize, String)> = Vec::new();
+    let mut start = None;
+    for (col, chr) in label_chars.iter().enumerate() {
+        match (chr.is_whitespace(), start) {
+            (false, None) => start = Some(col),
+            (true, Some(s)) => {
+                labels.push((s, label_chars[s..col].iter().collect()));
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    if labels.is_empty() {
+        return Err(format!("Failed to find labels in '{}'", label_row));
+    }
+    for (idx, (_, label)) in labels.iter().enumerate() {
+        if labels[..idx].iter().any(|(_, l)| l == label) {
+            return Err(format!("Found duplicate column label '{}'", label));
+        }
+    }
+    if labels.iter().all(|(_, l)| l.parse::<usize>().is_ok()) {
+        for (idx, (_, label)) in labels.iter().enumerate() {
+            if label.parse::<usize>() != Ok(idx + 1) {
+                return Err(format!(
+                    "Expected column label {} but found '{}'",
+                    idx + 1,
+                    label
+                ));
+            }
+        }
+    }
+    let column_of = |col: usize| {
+        labels
+            .iter()
+            .position(|(start, label)| (*start..start + label.chars().count()).contains(&col))
+    };
+
+    // Read the rows from the bottom up, so that each item lands on top of the
+    // ones below it.
+    let mut stacks = vec![Vec::new(); labels.len()];
+    for (row_idx, row) in item_rows.iter().enumerate().rev() {
+        let chars: Vec<char> = row.chars().collect();
+        let mut col = 0;
+        while col < chars.len() {
+            match chars[col..] {
+                [' ', ..] => col += 1,
+                ['[', item, ']', ..] => {
+                    let stack = match column_of(col + 1) {
+                        Some(s) => s,
+                        None => {
+                            return Err(format!(
+                                "Found item '{}' in row {} that isn't above a label",
+                                item,
+                                row_idx + 1
+                            ))
+                        }
+                    };
+                    if stacks[stack].len() != item_rows.len() - 1 - row_idx {
+                        return Err(format!(
+                            "Found item '{}' in row {} with a gap below it",
+                            item,
+                            row_idx + 1
+                        ));
+                    }
+                    stacks[stack].push(item);
+                    col += 3;
+                }
+                _ => {
+                    return Err(format!(
+                        "Found bad item in row {} at column {}: '{}'",
+                        row_idx + 1,
+                        col + 1,
+                        row
+                    ))
+                }
+            }
+        }
+    }
+
+    Ok(ColumnDiagram {
+        labels: labels.into_iter().map(|(_, l)| l).collect(),
+        stacks,
+    })
+}
+
+#[test]
+fn test_sections() {
+    let expected = vec![vec!["a", "b"], vec!["c"], vec!["d", "e"]];
+    assert_eq!(sections("a\nb\n\nc\n\nd\ne\n"), expected);
+    assert_eq!(sections("a\nb\n\nc\n\nd\ne"), expected);
+    assert_eq!(sections("a\r\nb\r\n\r\nc\r\n\r\nd\r\ne\r\n"), expected);
+    assert_eq!(sections("\n\na\nb\n\n\n\nc\n  \nd\ne\n\n\n"), expected);
+    assert_eq!(sections(""), Vec::<Vec<&str>>::new());
+    assert_eq!(sections("  x  \n"), vec![vec!["  x  "]]);
+}
+
+#[test]
+fn test_read_sections() {
+    for input in [
+        "a\nb\n\nc\n\nd\ne\n",
+        "a\r\nb\r\n\r\nc\r\n\r\nd\r\ne",
+        "\n\na\nb\n\n\n\nc\n  \nd\ne\n\n\n",
+    ] {
+        let mut reader = std::io::BufReader::with_capacity(3, input.as_bytes());
+        let read: Result<Vec<Vec<String>>, String> = read_sections(&mut reader).collect();
+        assert_eq!(
+            read,
+            Ok(sections(input)
+                .iter()
+                .map(|s| s.iter().map(|l| l.to_string()).collect())
+                .collect())
+        );
+    }
+    // Invalid UTF-8 is a read error, which is passed along.
+    let mut input = &b"a\n\n\xff\n"[..];
+    let mut read = read_sections(&mut input);
+    assert_eq!(read.next(), Some(Ok(vec!["a".to_string()])));
+    assert!(matches!(read.next(), Some(Err(_))));
+}
+
+#[cfg(test)]
+const CRATE_DIAGRAM: [&str; 4] = ["    [D]    ", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 "];
+
+#[test]
+fn test_parse_column_diagram() {
+    let diagram = parse_column_diagram(&CRATE_DIAGRAM).unwrap();
+    assert_eq!(diagram.labels, vec!["1", "2", "3"]);
+    assert_eq!(
+        diagram.stacks,
+        vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']]
+    );
+    assert_eq!(diagram.index_of("3"), Some(2));
+    assert_eq!(diagram.index_of("4"), None);
+
+    // Trailing spaces are optional, and labels don't have to be numbers.
+    let diagram = parse_column_diagram(&["    [D]", "[N] [C]", " a   b   c"]).unwrap();
+    assert_eq!(diagram.stacks, vec![vec!['N'], vec!['C', 'D'], vec![]]);
+}
+
+#[test]
+fn test_parse_column_diagram_errors() {
+    for bad in [
+        vec![],
+        vec!["[A]", "   "],
+        vec!["[A]", " 1   1 "],
+        vec!["[A]", " 1   3 "],
+        vec!["  [A]", " 1   2 "],
+        vec!["[A]    ", "    [B]", " 1   2 "],
+        vec!["[A] B", " 1   2 "],
+    ] {
+        assert!(parse_column_diagram(&bad).is_err(), "{:?} should fail", bad);
+    }
+}
@@ -0,0 +1,280 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A point (or vector) on the integer grid.
+///
+/// The y axis points down, as it does when a puzzle input is read line by
+/// line, so moving `Up` decreases y.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point2 {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point2 {
+    pub const ORIGIN: Point2 = Point2 { x: 0, y: 0 };
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        Point2 { x, y }
+    }
+
+    #[allow(dead_code)]
+    pub fn manhattan_distance(&self, other: Point2) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    #[allow(dead_code)]
+    pub fn chebyshev_distance(&self, other: Point2) -> u64 {
+        u64::max(self.x.abs_diff(other.x), self.y.abs_diff(other.y))
+    }
+
+    /// Rotate a quarter turn counterclockwise (as seen on screen) about the
+    /// origin.
+    #[allow(dead_code)]
+    pub fn turn_left(&self) -> Self {
+        Point2::new(self.y, -self.x)
+    }
+
+    /// Rotate a quarter turn clockwise (as seen on screen) about the origin.
+    #[allow(dead_code)]
+    pub fn turn_right(&self) -> Self {
+        Point2::new(-self.y, self.x)
+    }
+
+    /// The four points that share an edge with this one.
+    #[allow(dead_code)]
+    pub fn orthogonal_neighbors(&self) -> [Point2; 4] {
+        Direction::ALL.map(|d| *self + d.unit_vector())
+    }
+}
+
+impl Add for Point2 {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Point2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point2 {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Point2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<i64> for Point2 {
+    type Output = Self;
+    fn mul(self, scale: i64) -> Self {
+        Point2::new(self.x * scale, self.y * scale)
+    }
+}
+
+impl Neg for Point2 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Point2::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Point2 {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Point2 {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+/// A point (or vector) on the integer lattice in three dimensions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[allow(dead_code)]
+pub struct Point3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+#[allow(dead_code)]
+impl Point3 {
+    pub const fn new(x: i64, y: i64, z: i64) -> Self {
+        Point3 { x, y, z }
+    }
+
+    pub fn manhattan_distance(&self, other: Point3) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y) + self.z.abs_diff(other.z)
+    }
+
+    pub fn chebyshev_distance(&self, other: Point3) -> u64 {
+        self.x
+            .abs_diff(other.x)
+            .max(self.y.abs_diff(other.y))
+            .max(self.z.abs_diff(other.z))
+    }
+}
+
+impl Add for Point3 {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Point3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Point3 {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Point3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<i64> for Point3 {
+    type Output = Self;
+    fn mul(self, scale: i64) -> Self {
+        Point3::new(self.x * scale, self.y * scale, self.z * scale)
+    }
+}
+
+impl Neg for Point3 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Point3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl AddAssign for Point3 {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Point3 {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+/// One of the four grid directions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// All four directions in clockwise order, starting from `Up`.
+    #[allow(dead_code)]
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    #[allow(dead_code)]
+    pub fn turn_left(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Right => Direction::Up,
+            Direction::Down => Direction::Right,
+            Direction::Left => Direction::Down,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn turn_right(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn reverse(&self) -> Self {
+        self.turn_left().turn_left()
+    }
+
+    pub fn unit_vector(&self) -> Point2 {
+        match self {
+            Direction::Up => Point2::new(0, -1),
+            Direction::Right => Point2::new(1, 0),
+            Direction::Down => Point2::new(0, 1),
+            Direction::Left => Point2::new(-1, 0),
+        }
+    }
+}
+
+/// Directions can be written as arrows (`^v<>`), as `UDLR`, or as compass
+/// points (`NESW`), where north is up.
+impl TryFrom<char> for Direction {
+    type Error = String;
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '^' | 'U' | 'N' => Ok(Direction::Up),
+            '>' | 'R' | 'E' => Ok(Direction::Right),
+            'v' | 'D' | 'S' => Ok(Direction::Down),
+            '<' | 'L' | 'W' => Ok(Direction::Left),
+            c => Err(format!("Found bad direction: '{}'", c)),
+        }
+    }
+}
+
+impl From<Direction> for Point2 {
+    fn from(d: Direction) -> Self {
+        d.unit_vector()
+    }
+}
+
+#[test]
+fn test_point2_arithmetic() {
+    let a = Point2::new(3, -4);
+    let b = Point2::new(-1, 2);
+    assert_eq!(a + b, Point2::new(2, -2));
+    assert_eq!(a - b, Point2::new(4, -6));
+    assert_eq!(a * 3, Point2::new(9, -12));
+    assert_eq!(-a, Point2::new(-3, 4));
+    assert_eq!(a.manhattan_distance(b), 10);
+    assert_eq!(a.chebyshev_distance(b), 6);
+    assert_eq!(a.manhattan_distance(Point2::ORIGIN), 7);
+}
+
+#[test]
+fn test_point3_arithmetic() {
+    let a = Point3::new(1, 2, 3);
+    let b = Point3::new(-2, 2, 7);
+    assert_eq!(a + b, Point3::new(-1, 4, 10));
+    assert_eq!(a - b, Point3::new(3, 0, -4));
+    assert_eq!(a.manhattan_distance(b), 7);
+    assert_eq!(a.chebyshev_distance(b), 4);
+}
+
+#[test]
+fn test_turns() {
+    for d in Direction::ALL {
+        assert_eq!(d.turn_left().turn_right(), d);
+        assert_eq!(d.reverse().reverse(), d);
+        assert_eq!(d.unit_vector().turn_left(), d.turn_left().unit_vector());
+        assert_eq!(d.unit_vector().turn_right(), d.turn_right().unit_vector());
+        assert_eq!(-d.unit_vector(), d.reverse().unit_vector());
+    }
+    assert_eq!(Direction::Up.turn_right(), Direction::Right);
+}
+
+#[test]
+fn test_parse_direction() {
+    for (chars, direction) in [
+        ("^UN", Direction::Up),
+        (">RE", Direction::Right),
+        ("vDS", Direction::Down),
+        ("<LW", Direction::Left),
+    ] {
+        for c in chars.chars() {
+            assert_eq!(Direction::try_from(c), Ok(direction));
+        }
+    }
+    assert!(Direction::try_from('x').is_err());
+}
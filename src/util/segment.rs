@@ -0,0 +1,446 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::util::number_theory::gcd;
+use crate::util::point::Point2;
+
+/// A closed line segment between two points on the integer grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Segment {
+    pub start: Point2,
+    pub end: Point2,
+}
+
+/// How two segments meet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Intersection {
+    /// The segments cross or touch at a single lattice point.
+    Point(Point2),
+    /// The segments are collinear and share more than one point.
+    Overlap(Segment),
+    /// The segments cross at a single point that isn't on the integer grid.
+    OffLattice,
+}
+
+fn cross(a: Point2, b: Point2) -> i128 {
+    a.x as i128 * b.y as i128 - a.y as i128 * b.x as i128
+}
+
+fn dot(a: Point2, b: Point2) -> i128 {
+    a.x as i128 * b.x as i128 + a.y as i128 * b.y as i128
+}
+
+impl Segment {
+    pub fn new(start: Point2, end: Point2) -> Self {
+        Segment { start, end }
+    }
+
+    pub fn is_point(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.start.y == self.end.y
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.start.x == self.end.x
+    }
+
+    /// Whether the segment runs at exactly 45 degrees.
+    pub fn is_diagonal(&self) -> bool {
+        let d = self.end - self.start;
+        d.x != 0 && d.x.abs() == d.y.abs()
+    }
+
+    /// The smallest step that moves from one lattice point on the segment to
+    /// the next. This is zero for a segment that is a single point.
+    pub fn step(&self) -> Point2 {
+        let d = self.end - self.start;
+        match gcd(d.x.unsigned_abs(), d.y.unsigned_abs()) as i64 {
+            0 => Point2::ORIGIN,
+            g => Point2::new(d.x / g, d.y / g),
+        }
+    }
+
+    /// The number of lattice points that lie exactly on the segment.
+    #[allow(dead_code)]
+    pub fn lattice_point_count(&self) -> u64 {
+        let d = self.end - self.start;
+        gcd(d.x.unsigned_abs(), d.y.unsigned_abs()) + 1
+    }
+
+    /// Every lattice point that lies exactly on the segment, from `start` to
+    /// `end`. For horizontal, vertical and diagonal segments, this is every
+    /// grid cell the segment passes through.
+    #[allow(dead_code)]
+    pub fn lattice_points(&self) -> impl Iterator<Item = Point2> {
+        let (start, step) = (self.start, self.step());
+        (0..self.lattice_point_count() as i64).map(move |i| start + step * i)
+    }
+
+    /// The grid cells closest to the segment, one for each step along its
+    /// longer axis, as chosen by Bresenham's algorithm.
+    #[allow(dead_code)]
+    pub fn bresenham(&self) -> Bresenham {
+        let d = self.end - self.start;
+        Bresenham {
+            current: self.start,
+            end: self.end,
+            dx: d.x.abs(),
+            dy: -d.y.abs(),
+            sx: d.x.signum(),
+            sy: d.y.signum(),
+            error: d.x.abs() - d.y.abs(),
+            done: false,
+        }
+    }
+
+    pub fn contains(&self, point: Point2) -> bool {
+        cross(point - self.start, self.end - self.start) == 0
+            && i64::min(self.start.x, self.end.x) <= point.x
+            && point.x <= i64::max(self.start.x, self.end.x)
+            && i64::min(self.start.y, self.end.y) <= point.y
+            && point.y <= i64::max(self.start.y, self.end.y)
+    }
+
+    /// Find where this segment meets another one, if it does at all.
+    pub fn intersection(&self, other: &Segment) -> Option<Intersection> {
+        if self.is_point() {
+            return other
+                .contains(self.start)
+                .then_some(Intersection::Point(self.start));
+        }
+        if other.is_point() {
+            return self
+                .contains(other.start)
+                .then_some(Intersection::Point(other.start));
+        }
+        let (r, s) = (self.end - self.start, other.end - other.start);
+        let offset = other.start - self.start;
+        let mut denominator = cross(r, s);
+        if denominator == 0 {
+            if cross(offset, r) != 0 {
+                // parallel, but on different lines
+                return None;
+            }
+            // Collinear, so the overlap (if any) runs between two of the four
+            // endpoints. Order them by their position along the line.
+            let key = |p: &Point2| dot(*p, r);
+            let (self_low, self_high) = sorted_by_key(self.start, self.end, key);
+            let (other_low, other_high) = sorted_by_key(other.start, other.end, key);
+            let low = if key(&self_low) >= key(&other_low) {
+                self_low
+            } else {
+                other_low
+            };
+            let high = if key(&self_high) <= key(&other_high) {
+                self_high
+            } else {
+                other_high
+            };
+            return match key(&low).cmp(&key(&high)) {
+                std::cmp::Ordering::Greater => None,
+                std::cmp::Ordering::Equal => Some(Intersection::Point(low)),
+                std::cmp::Ordering::Less => Some(Intersection::Overlap(Segment::new(low, high))),
+            };
+        }
+        // Solve self.start + t * r == other.start + u * s, with t and u
+        // scaled up by the denominator to keep everything in integers.
+        let (mut t, mut u) = (cross(offset, s), cross(offset, r));
+        if denominator < 0 {
+            (denominator, t, u) = (-denominator, -t, -u);
+        }
+        if t < 0 || t > denominator || u < 0 || u > denominator {
+            return None;
+        }
+        let (x, y) = (r.x as i128 * t, r.y as i128 * t);
+        if x % denominator != 0 || y % denominator != 0 {
+            return Some(Intersection::OffLattice);
+        }
+        Some(Intersection::Point(
+            self.start + Point2::new((x / denominator) as i64, (y / denominator) as i64),
+        ))
+    }
+
+    /// Whether the segments share at least one point, on the grid or not.
+    #[allow(dead_code)]
+    pub fn intersects(&self, other: &Segment) -> bool {
+        self.intersection(other).is_some()
+    }
+}
+
+fn sorted_by_key<F>(a: Point2, b: Point2, key: F) -> (Point2, Point2)
+where
+    F: Fn(&Point2) -> i128,
+{
+    if key(&a) <= key(&b) {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Iterator over the cells chosen by [`Segment::bresenham`].
+#[allow(dead_code)]
+pub struct Bresenham {
+    current: Point2,
+    end: Point2,
+    dx: i64,
+    dy: i64,
+    sx: i64,
+    sy: i64,
+    error: i64,
+    done: bool,
+}
+
+impl Iterator for Bresenham {
+    type Item = Point2;
+
+    fn next(&mut self) -> Option<Point2> {
+        if self.done {
+            return None;
+        }
+        let point = self.current;
+        if point == self.end {
+            self.done = true;
+            return Some(point);
+        }
+        let doubled_error = 2 * self.error;
+        if doubled_error >= self.dy {
+            self.error += self.dy;
+            self.current.x += self.sx;
+        }
+        if doubled_error <= self.dx {
+            self.error += self.dx;
+            self.current.y += self.sy;
+        }
+        Some(point)
+    }
+}
+
+/// Identifies the infinite line a segment lies on, along with the segment's
+/// extent along that line measured in lattice steps.
+struct LinePosition {
+    line: (Point2, i128),
+    low: i128,
+    high: i128,
+}
+
+/// The lattice direction of a segment, pointing in a consistent direction so
+/// that segments on the same line agree.
+fn canonical_step(segment: &Segment) -> Point2 {
+    let step = segment.step();
+    if step == Point2::ORIGIN {
+        Point2::new(1, 0)
+    } else if step.x < 0 || (step.x == 0 && step.y < 0) {
+        -step
+    } else {
+        step
+    }
+}
+
+fn position_on_line(step: Point2, point: Point2) -> i128 {
+    // Consecutive lattice points on a line differ by the same amount in the
+    // dot product, so dividing it out gives their index along the line.
+    dot(point, step).div_euclid(dot(step, step))
+}
+
+fn line_position(segment: &Segment) -> LinePosition {
+    let step = canonical_step(segment);
+    let (a, b) = (
+        position_on_line(step, segment.start),
+        position_on_line(step, segment.end),
+    );
+    LinePosition {
+        line: (step, cross(step, segment.start)),
+        low: i128::min(a, b),
+        high: i128::max(a, b),
+    }
+}
+
+/// Count the lattice points that are covered by at least two segments.
+///
+/// Collinear overlaps are found with a sweep along each line, and segments on
+/// different lines are only compared while a sweep across the x axis says
+/// their x ranges overlap. No individual points are stored except where two
+/// different lines cross, so the running time doesn't depend on how long the
+/// segments are. It is quadratic in the number of segments when most of their
+/// x ranges overlap, since every such pair is compared.
+pub fn count_overlaps(segments: &[Segment]) -> u64 {
+    // Sweep along each line to find the ranges covered more than once.
+    let mut lines: HashMap<(Point2, i128), Vec<(i128, i128)>> = HashMap::new();
+    for segment in segments {
+        let LinePosition { line, low, high } = line_position(segment);
+        lines.entry(line).or_default().push((low, high));
+    }
+    let mut overlaps: HashMap<(Point2, i128), Vec<(i128, i128)>> = HashMap::new();
+    let mut total = 0;
+    for (line, extents) in lines {
+        let mut events: Vec<(i128, i64)> = Vec::with_capacity(extents.len() * 2);
+        for (low, high) in extents {
+            events.push((low, 1));
+            events.push((high + 1, -1));
+        }
+        events.sort_unstable();
+        let mut ranges = Vec::new();
+        let mut depth = 0;
+        let mut overlap_start = 0;
+        for (position, change) in events {
+            let was_overlapping = depth >= 2;
+            depth += change;
+            if !was_overlapping && depth >= 2 {
+                overlap_start = position;
+            } else if was_overlapping && depth < 2 {
+                total += (position - overlap_start) as u64;
+                ranges.push((overlap_start, position - 1));
+            }
+        }
+        if !ranges.is_empty() {
+            overlaps.insert(line, ranges);
+        }
+    }
+
+    // Sweep across the x axis to find where segments on different lines cross.
+    let mut order: Vec<(&Segment, (Point2, i128))> = segments
+        .iter()
+        .map(|s| (s, line_position(s).line))
+        .collect();
+    order.sort_unstable_by_key(|(s, _)| i64::min(s.start.x, s.end.x));
+    let mut active: Vec<(&Segment, (Point2, i128))> = Vec::new();
+    let mut crossings = HashSet::new();
+    for (segment, line) in order {
+        let left = i64::min(segment.start.x, segment.end.x);
+        active.retain(|(s, _)| i64::max(s.start.x, s.end.x) >= left);
+        for (other, other_line) in active.iter() {
+            if line == *other_line {
+                continue;
+            }
+            if let Some(Intersection::Point(p)) = segment.intersection(other) {
+                crossings.insert(p);
+            }
+        }
+        active.push((segment, line));
+    }
+
+    // A crossing may already have been counted as part of a collinear overlap
+    // on one or more of the lines through it. Make sure each one is counted
+    // exactly once.
+    let steps: HashSet<Point2> = overlaps.keys().map(|(step, _)| *step).collect();
+    for p in crossings {
+        let mut times_counted = 0;
+        for step in steps.iter() {
+            if let Some(ranges) = overlaps.get(&(*step, cross(*step, p))) {
+                let position = position_on_line(*step, p);
+                let idx = ranges.partition_point(|(_, high)| *high < position);
+                if idx < ranges.len() && ranges[idx].0 <= position {
+                    times_counted += 1;
+                }
+            }
+        }
+        total = (total + 1) - times_counted;
+    }
+    total
+}
+
+#[cfg(test)]
+fn segment(x1: i64, y1: i64, x2: i64, y2: i64) -> Segment {
+    Segment::new(Point2::new(x1, y1), Point2::new(x2, y2))
+}
+
+#[test]
+fn test_lattice_points() {
+    let points: Vec<Point2> = segment(1, 1, 3, 3).lattice_points().collect();
+    assert_eq!(
+        points,
+        vec![Point2::new(1, 1), Point2::new(2, 2), Point2::new(3, 3)]
+    );
+    assert_eq!(segment(0, 0, 6, 4).lattice_point_count(), 3);
+    assert_eq!(segment(9, 7, 7, 7).lattice_points().count(), 3);
+    assert_eq!(segment(5, 5, 5, 5).lattice_points().count(), 1);
+}
+
+#[test]
+fn test_bresenham() {
+    let points: Vec<Point2> = segment(0, 0, 5, 2).bresenham().collect();
+    assert_eq!(
+        points,
+        vec![
+            Point2::new(0, 0),
+            Point2::new(1, 0),
+            Point2::new(2, 1),
+            Point2::new(3, 1),
+            Point2::new(4, 2),
+            Point2::new(5, 2),
+        ]
+    );
+    // Bresenham and exact rasterisation agree on axis-aligned and diagonal
+    // segments.
+    for s in [
+        segment(3, 9, 3, 2),
+        segment(8, 0, 0, 8),
+        segment(2, 2, 2, 2),
+    ] {
+        assert!(s.bresenham().eq(s.lattice_points()));
+    }
+}
+
+#[test]
+fn test_intersection() {
+    assert_eq!(
+        segment(0, 0, 4, 4).intersection(&segment(0, 4, 4, 0)),
+        Some(Intersection::Point(Point2::new(2, 2)))
+    );
+    assert_eq!(
+        segment(0, 0, 1, 1).intersection(&segment(0, 1, 1, 0)),
+        Some(Intersection::OffLattice)
+    );
+    assert_eq!(
+        segment(0, 0, 5, 0).intersection(&segment(7, 0, 3, 0)),
+        Some(Intersection::Overlap(segment(3, 0, 5, 0)))
+    );
+    assert_eq!(
+        segment(0, 0, 5, 0).intersection(&segment(5, 0, 9, 0)),
+        Some(Intersection::Point(Point2::new(5, 0)))
+    );
+    assert_eq!(segment(0, 0, 5, 0).intersection(&segment(0, 1, 5, 1)), None);
+    assert_eq!(segment(0, 0, 2, 2).intersection(&segment(3, 0, 3, 9)), None);
+    assert!(segment(0, 0, 4, 0).intersects(&segment(2, 0, 2, 0)));
+}
+
+#[test]
+fn test_count_overlaps() {
+    // Compare against a brute-force count of every point.
+    let segments = vec![
+        segment(0, 9, 5, 9),
+        segment(8, 0, 0, 8),
+        segment(9, 4, 3, 4),
+        segment(2, 2, 2, 1),
+        segment(7, 0, 7, 4),
+        segment(6, 4, 2, 0),
+        segment(0, 9, 2, 9),
+        segment(3, 4, 1, 4),
+        segment(0, 0, 8, 8),
+        segment(5, 5, 8, 2),
+        segment(0, 0, 6, 3),
+        segment(4, 4, 4, 4),
+    ];
+    let mut counts: HashMap<Point2, u32> = HashMap::new();
+    for s in segments.iter() {
+        for p in s.lattice_points() {
+            *counts.entry(p).or_default() += 1;
+        }
+    }
+    let expected = counts.values().filter(|c| **c >= 2).count() as u64;
+    assert_eq!(count_overlaps(&segments), expected);
+}
+
+#[test]
+fn test_count_overlaps_large_coordinates() {
+    let segments = vec![
+        segment(0, 0, 1_000_000_000, 0),
+        segment(500_000_000, 0, 2_000_000_000, 0),
+        segment(600_000_000, -5, 600_000_000, 5),
+        segment(0, -7, 14, 7),
+    ];
+    assert_eq!(count_overlaps(&segments), 500_000_001 + 1);
+}